@@ -0,0 +1,86 @@
+#![no_main]
+
+//! Wraps and truncates arbitrary text, checking that no grapheme is lost or duplicated and
+//! that no line is wider than the limit. Run with `cargo fuzz run wrap`.
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use o4t::wrap::{LineComposer, LineTruncator, WordWrapper};
+use ratatui::layout::Alignment;
+use ratatui::style::{Color, Style};
+use ratatui::text::StyledGrapheme;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    lines: Vec<String>,
+    width: u8,
+    trim: bool,
+    horizontal_offset: u8,
+}
+
+// Every grapheme gets a unique colour, so we can tell where each output grapheme came from.
+fn tag(grapheme: &StyledGrapheme) -> u32 {
+    match grapheme.style.fg {
+        Some(Color::Rgb(r, g, b)) => u32::from_be_bytes([0, r, g, b]),
+        _ => panic!("untagged grapheme {:?}", grapheme),
+    }
+}
+
+fn is_whitespace(symbol: &str) -> bool {
+    symbol == "\u{200b}" || symbol.chars().all(char::is_whitespace) && symbol != "\u{00a0}"
+}
+
+fn check_lines<'a>(mut composer: impl LineComposer<'a>, width: u16) -> Vec<u32> {
+    let mut tags = vec![];
+    while let Some(line) = composer.next_line() {
+        let actual_width: u16 = line.line.iter().map(|g| g.symbol.width() as u16).sum();
+        assert_eq!(line.width, actual_width);
+        assert!(line.width <= width, "line wider than {}: {:?}", width, line.line);
+        tags.extend(line.line.iter().map(tag));
+    }
+    tags
+}
+
+fuzz_target!(|input: Input| {
+    let width = u16::from(input.width);
+    let mut index = 0u32;
+    let lines = input
+        .lines
+        .iter()
+        .map(|line| {
+            line.graphemes(true)
+                .map(|symbol| {
+                    let [_, r, g, b] = index.to_be_bytes();
+                    index += 1;
+                    StyledGrapheme::new(symbol, Style::default().fg(Color::Rgb(r, g, b)))
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let input_lines = || {
+        lines
+            .iter()
+            .map(|line| (line.clone().into_iter(), Alignment::Left))
+    };
+
+    let tags = check_lines(WordWrapper::new(input_lines(), width, input.trim), width);
+    assert!(tags.windows(2).all(|pair| pair[0] < pair[1]), "graphemes out of order");
+    if width > 0 {
+        for grapheme in lines.iter().flatten() {
+            let droppable =
+                is_whitespace(grapheme.symbol) || grapheme.symbol.width() as u16 > width;
+            assert!(
+                droppable || tags.binary_search(&tag(grapheme)).is_ok(),
+                "lost {:?}",
+                grapheme
+            );
+        }
+    }
+
+    let mut truncator = LineTruncator::new(input_lines(), width);
+    truncator.set_horizontal_offset(u16::from(input.horizontal_offset));
+    let tags = check_lines(truncator, width);
+    assert!(tags.windows(2).all(|pair| pair[0] < pair[1]), "graphemes out of order");
+});
@@ -1,13 +1,20 @@
-use crate::config::Config;
-use crate::theme::Theme;
-use crate::words;
+use crate::book::Book;
+use crate::config::{settings, Config, ConfigSource, Setting, SettingKind, SettingValue};
+use crate::crash::set_current_config;
+use crate::fuzzy::fuzzy_filter;
+use crate::history::{GameRecord, Stats};
+use crate::terminal::{Background, ColorDepth};
+use crate::theme::{find_theme, Theme};
 use clap::ValueEnum;
 use derive_setters::Setters;
-use rand::seq::IteratorRandom;
+use o4t::clock::Clock;
+use o4t::game::{FailReason, Game, GameConfig, GameState, Mode, WordList};
+use o4t::generator::{NoWords, Punctuated, RandomWords, TextWords, WordGenerator};
 use ratatui::prelude::Color;
 use ratatui::style::{Style, Stylize};
 use serde::{Deserialize, Serialize};
-use std::ops::Div;
+use std::cmp::max;
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::time::Duration;
 use tachyonfx::Interpolation::QuadOut;
@@ -16,76 +23,106 @@ use tachyonfx::{fx, Effect};
 pub enum Screen {
     Game,
     Results,
+    // The game ended early because one of the configured fail conditions was met.
+    Failed(FailReason),
+    // Lists every config option, which can be changed in place.
+    Settings,
+    // A summary of the games in the history file.
+    Stats(Stats),
 }
 
-const NUMBER_OF_WORDS_TO_PICK: usize = 500;
-#[derive(Debug, PartialOrd, PartialEq)]
-pub struct WordAttempt {
-    // the word the user was asked and attempted to type
-    pub word: String,
-    // what the user typed for this word
-    pub user_attempt: String,
+// The game durations offered by the command palette, in seconds.
+const PALETTE_DURATIONS: [usize; 4] = [15, 30, 60, 120];
+// The target wpm used when the ghost is turned on from the command palette.
+const DEFAULT_GHOST_WPM: usize = 60;
+pub struct ThemePicker {
+    // The text typed by the user to fuzzy filter the list of themes.
+    pub filter: String,
+    // The index of the highlighted theme within the filtered themes.
+    pub selected: usize,
+    // The theme that was active when the picker was opened, restored if the user cancels.
+    pub original_theme_name: String,
 }
 
-#[derive(Debug, Default)]
-pub struct Score {
-    // Number of characters matching what they should be at the current point in time.
-    pub character_matches: usize,
-    // Number of characters which don't match what they should be at the current point in time.
-    // This value can decrease if the user corrects a typo.
-    pub character_mismatches: usize,
-    // The number of correctly typed characters in this session. Always increasing.
-    pub character_hits: usize,
-    // The number of characters which were typed which shouldn't have been in this session.
-    // This number cannot decrease. If you make a typo, it remains in this value.
-    pub character_misses: usize,
-    // The ratio of character_hits / character_hits + character_misses
-    pub accuracy: f32,
-    // Number of characters typed per minute.
-    pub chars_per_minute: f32,
-    // WPM = (character_matches * 5) * (60 / session_length_secs)
-    pub wpm: f32,
-    // Number of words typed CORRECTLY per minute.
-    pub real_words_per_minute: f32,
-    // Total number of CORRECTLY typed words.
-    pub num_words: usize,
-    // The number of words typed correctly in a row. Always increasing. Words that were typed
-    // incorrectly then changed don't count.
-    pub best_char_streak: usize,
-    pub current_char_streak: usize,
+/// Something that can be done from the command palette.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaletteAction {
+    SetTime(usize),
+    SetMode(Mode),
+    SetWordList(WordList),
+    TogglePunctuation,
+    PickTheme,
+    ToggleGhost,
+    OpenSettings,
+    OpenStats,
 }
 
-impl Score {
-    pub fn is_perfect(&self) -> bool {
-        self.character_misses == 0 && self.num_words > 0
+impl PaletteAction {
+    pub fn all() -> Vec<PaletteAction> {
+        let mut actions = PALETTE_DURATIONS
+            .into_iter()
+            .map(PaletteAction::SetTime)
+            .collect::<Vec<_>>();
+        actions.extend(Mode::value_variants().iter().map(|&mode| PaletteAction::SetMode(mode)));
+        actions.extend(
+            WordList::value_variants()
+                .iter()
+                .map(|&word_list| PaletteAction::SetWordList(word_list)),
+        );
+        actions.extend([
+            PaletteAction::TogglePunctuation,
+            PaletteAction::PickTheme,
+            PaletteAction::ToggleGhost,
+            PaletteAction::OpenSettings,
+            PaletteAction::OpenStats,
+        ]);
+        actions
     }
-}
 
-impl WordAttempt {
-    pub fn new(word: String) -> WordAttempt {
-        WordAttempt {
-            word,
-            user_attempt: String::new(),
+    pub fn label(&self) -> String {
+        match self {
+            PaletteAction::SetTime(secs) => format!("duration: {}s", secs),
+            PaletteAction::SetMode(mode) => format!("mode: {}", value_name(mode)),
+            PaletteAction::SetWordList(word_list) => {
+                format!("word list: {}", value_name(word_list))
+            }
+            PaletteAction::TogglePunctuation => "toggle punctuation".to_string(),
+            PaletteAction::PickTheme => "pick theme".to_string(),
+            PaletteAction::ToggleGhost => "toggle ghost".to_string(),
+            PaletteAction::OpenSettings => "open settings".to_string(),
+            PaletteAction::OpenStats => "open stats".to_string(),
         }
     }
 }
 
+// The name of an enum value as it's written in config.toml and on the command line.
+fn value_name<T: ValueEnum>(value: &T) -> String {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// An enum config value, as it's stored on the settings screen.
+pub fn value_setting<T: ValueEnum>(value: T) -> SettingValue {
+    SettingValue::Text(value_name(&value))
+}
+
+pub struct CommandPalette {
+    // The text typed by the user to fuzzy filter the list of actions.
+    pub filter: String,
+    // The index of the highlighted action within the filtered actions.
+    pub selected: usize,
+}
+
 // Holds the state for the app
 #[derive(Setters)]
 pub struct App {
-    // the current input the user has typed while trying to type words[0]
-    pub current_user_input: String,
-    // The index of the word in words that is being attempted by the user
-    pub current_word_offset: usize,
-    // contains the history of words for the current session.
-    // the current word the user is being asked to type is words[0]
-    pub words: Vec<WordAttempt>,
+    // The current game, which holds the words and what the user has typed.
+    pub game: Game,
     pub current_screen: Screen,
-    pub time_remaining: Duration,
-    pub game_active: bool,
-    pub millis_at_current_game_start: u64,
-    pub current_millis: u64,
-    pub score: Score,
+    // Where the time comes from, for games and anything else timed.
+    pub clock: Rc<dyn Clock>,
     pub load_results_screen_effect: Effect,
     pub load_words_effect: Effect,
     pub last_tick_duration: Duration,
@@ -94,14 +131,42 @@ pub struct App {
     // Debug string that can be rendered to screen
     pub debug_string: String,
 
+    // The theme being shown, which is config.theme unless it's "auto" or being previewed.
     pub theme_name: String,
+    // The terminal's background colour, which picks the theme when config.theme is "auto".
+    #[setters(skip)]
+    pub background: Option<Background>,
+    // The theme picker overlay, when it's open.
+    pub theme_picker: Option<ThemePicker>,
+    // The command palette overlay, when it's open.
+    pub command_palette: Option<CommandPalette>,
     pub cursor_style: CursorType,
     pub themes: Vec<Theme>,
+    // The number of colours the terminal supports. Colours are downsampled to fit when drawing.
+    pub color_depth: ColorDepth,
     pub config: Rc<Config>,
+    // Where each config option's value came from, keyed by its name in config.toml.
+    // Options missing from the map have their default value.
+    pub config_sources: HashMap<String, ConfigSource>,
+    // The index of the highlighted option on the settings screen.
+    pub settings_selected: usize,
+
+    // The wrapped line the cursor is on, and when the cursor arrived on it. In memory mode,
+    // upcoming words are hidden once this line has been visible for config.memory seconds.
+    pub memory_row: u16,
+    pub memory_row_shown_at: Duration,
 
-    // The position of the ghost cursor (which moves based on config.target_wpm).
-    // The user can basically race against this cursor.
-    pub ghost_offset: Option<f64>
+    // Something that went wrong in the background (like saving the history), shown in the
+    // footer until the next key press.
+    pub error: Option<String>,
+    // The most recently finished game, printed on exit with --output.
+    pub last_game: Option<GameRecord>,
+    // Text given with --text or piped to stdin, which is typed instead of generated words.
+    #[setters(skip)]
+    pub text: Option<Rc<str>>,
+    // The book being typed with `o4t book`, where each game is its current page.
+    #[setters(skip)]
+    pub book: Option<Book>,
 }
 
 pub fn load_words_effect(theme: Theme) -> Effect {
@@ -124,6 +189,19 @@ pub enum CursorType {
     None,
 }
 
+// How mistyped characters are marked, in addition to the theme's mismatch colour, so that
+// mistakes can be seen without relying on colour alone.
+#[derive(ValueEnum, Clone, Debug, Copy, PartialEq, Serialize, Deserialize)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorIndicator {
+    None,
+    Underline,
+    Reverse,
+    // Show the character that was actually typed in place of the expected character.
+    Glyph,
+}
+
 #[derive(ValueEnum, Clone, Debug, Copy, Serialize, Deserialize)]
 #[clap(rename_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
@@ -134,148 +212,362 @@ pub enum CurrentWord {
 }
 
 impl App {
-    pub fn with_config(config: Rc<Config>) -> App {
-        let theme_name = &config.theme;
-        let theme = get_theme(theme_name);
+    pub fn with_config(config: Rc<Config>, themes: Vec<Theme>, clock: Rc<dyn Clock>) -> App {
+        let theme_name = config.resolved_theme(None);
+        let theme = find_theme(&themes, theme_name)
+            .ok()
+            .or(themes.first())
+            .cloned()
+            .unwrap_or_default();
         App {
-            current_user_input: String::new(),
-            current_word_offset: 0,
-            words: generate_words(),
+            game: new_game(&config, None, clock.clone()),
             current_screen: Screen::Game,
-            time_remaining: Duration::from_secs(config.time as u64),
-            game_active: false,
-            millis_at_current_game_start: 0,
-            current_millis: 0,
-            score: Score::default(),
+            clock,
             load_words_effect: load_words_effect(theme.clone()),
             load_results_screen_effect: load_score_screen_effect(),
             last_tick_duration: Duration::ZERO,
             is_debug_mode: false, // TODO - make cli switch
             debug_string: "".to_string(),
             theme_name: theme_name.to_string(),
-            themes: get_themes(),
+            background: None,
+            theme_picker: None,
+            command_palette: None,
+            themes,
+            color_depth: config.colors.resolve(),
             cursor_style: config.cursor,
-            ghost_offset: if config.target_wpm == 0 { None } else { Some(0.) },
+            memory_row: 0,
+            memory_row_shown_at: Duration::ZERO,
+            error: None,
+            last_game: None,
+            text: None,
+            book: None,
             config,
+            config_sources: HashMap::new(),
+            settings_selected: 0,
         }
     }
 
-    pub fn next_theme(&mut self) {
-        let mut themes = self
-            .themes
+    /// The position of the ghost cursor, which moves at config.target_wpm. The user can
+    /// basically race against this cursor.
+    pub fn ghost_offset(&self) -> Option<f64> {
+        if self.config.target_wpm == 0 || self.game.is_zen_mode() {
+            return None;
+        }
+        let target_chars_per_minute = 5 * self.config.target_wpm;
+        let target_chars_per_second = target_chars_per_minute as f64 / 60.;
+        Some(target_chars_per_second * self.game.elapsed().as_secs_f64())
+    }
+
+    pub fn open_theme_picker(&mut self) {
+        self.theme_picker = Some(ThemePicker {
+            filter: String::new(),
+            selected: 0,
+            original_theme_name: self.theme_name.clone(),
+        });
+        // Start with the cursor on the current theme.
+        if let Some(index) = self
+            .theme_picker_matches()
             .iter()
-            .cycle()
-            .skip_while(|theme| theme.name != self.theme_name)
-            .skip(1);
+            .position(|name| *name == self.theme_name)
+        {
+            self.select_theme_in_picker(index as isize);
+        }
+    }
+
+    /// The names of the themes matching the theme picker's filter, best matches first.
+    pub fn theme_picker_matches(&self) -> Vec<String> {
+        let filter = self
+            .theme_picker
+            .as_ref()
+            .map_or("", |picker| picker.filter.as_str());
+        let names = self.themes.iter().map(|theme| theme.name.clone()).collect();
+        fuzzy_filter(filter, names, |name| name)
+    }
+
+    /// Moves the theme picker's selection by `delta`, wrapping around at either end, and
+    /// previews the newly selected theme.
+    pub fn move_theme_picker_selection(&mut self, delta: isize) {
+        if let Some(picker) = &self.theme_picker {
+            let selected = picker.selected as isize + delta;
+            self.select_theme_in_picker(selected);
+        }
+    }
+
+    pub fn set_theme_picker_filter(&mut self, filter: String) {
+        if let Some(picker) = &mut self.theme_picker {
+            picker.filter = filter;
+        }
+        self.select_theme_in_picker(0);
+    }
 
-        self.theme_name = themes.next().unwrap().name.to_string();
+    fn select_theme_in_picker(&mut self, index: isize) {
+        let matches = self.theme_picker_matches();
+        if let Some(picker) = &mut self.theme_picker {
+            if matches.is_empty() {
+                picker.selected = 0;
+                return;
+            }
+            picker.selected = index.rem_euclid(matches.len() as isize) as usize;
+            self.theme_name = matches[picker.selected].clone();
+        }
+    }
+
+    /// Closes the theme picker. If `confirm` is false the theme from before the picker was
+    /// opened is restored, otherwise the name of the chosen theme is returned.
+    pub fn close_theme_picker(&mut self, confirm: bool) -> Option<String> {
+        let has_matches = !self.theme_picker_matches().is_empty();
+        let picker = self.theme_picker.take()?;
+        if confirm && has_matches {
+            Some(self.theme_name.clone())
+        } else {
+            self.theme_name = picker.original_theme_name;
+            None
+        }
     }
 
     pub fn get_current_theme(&self) -> Theme {
-        self.themes
-            .iter()
-            .find(|t| t.name == self.theme_name)
-            .unwrap()
-            .clone()
+        if self.color_depth == ColorDepth::None {
+            return monochrome_theme();
+        }
+        // The theme name is validated when the config is loaded, but fall back to the first
+        // theme rather than panicking inside raw mode.
+        find_theme(&self.themes, &self.theme_name)
+            .ok()
+            .or(self.themes.first())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Types the given text instead of generated words, in this game and every game after it.
+    pub fn with_text(mut self, text: Rc<str>) -> App {
+        self.game = new_game(&self.config, Some(&text), self.clock.clone());
+        self.text = Some(text);
+        self
+    }
+
+    /// Shows the light or dark theme to suit the terminal's background, if the theme is "auto".
+    pub fn with_background(mut self, background: Option<Background>) -> App {
+        self.background = background;
+        self.theme_name = self.config.resolved_theme(background).to_string();
+        self.load_words_effect = load_words_effect(self.get_current_theme());
+        self
+    }
+
+    /// Types the current page of the book in this game, and the pages after it in every game
+    /// after it.
+    pub fn with_book(mut self, book: Book) -> App {
+        self.game = new_game(&self.config, Some(&book.page_text()), self.clock.clone());
+        self.book = Some(book);
+        self
     }
 
     pub fn reset_game(&mut self) {
         let config = self.config.clone();
-        *self = App::with_config(config).theme_name(self.theme_name.to_string());
+        let themes = self.themes.clone();
+        let app = App::with_config(config, themes, self.clock.clone())
+            .with_background(self.background)
+            .theme_name(self.theme_name.to_string())
+            .config_sources(self.config_sources.clone())
+            .last_game(self.last_game.take());
+        *self = match (self.book.take(), self.text.take()) {
+            (Some(book), _) => app.with_book(book),
+            (None, Some(text)) => app.with_text(text),
+            (None, None) => app,
+        };
         self.load_words_effect = load_words_effect(self.get_current_theme().clone());
     }
 
-    pub fn game_time_elapsed_millis(&self) -> u64 {
-        if self.game_active {
-            self.current_millis - self.millis_at_current_game_start
+    /// The options shown on the settings screen.
+    pub fn settings(&self) -> Vec<Setting> {
+        let theme_names = self
+            .themes
+            .iter()
+            .map(|theme| theme.name.clone())
+            .collect::<Vec<_>>();
+        settings(&theme_names)
+    }
+
+    pub fn config_source(&self, key: &str) -> ConfigSource {
+        self.config_sources
+            .get(key)
+            .copied()
+            .unwrap_or(ConfigSource::Default)
+    }
+
+    /// Shows the settings screen, abandoning the current game.
+    pub fn open_settings(&mut self) {
+        self.settings_selected = 0;
+        self.current_screen = Screen::Settings;
+    }
+
+    pub fn move_settings_selection(&mut self, delta: isize) {
+        let len = self.settings().len() as isize;
+        self.settings_selected = (self.settings_selected as isize + delta).rem_euclid(len) as usize;
+    }
+
+    /// Steps the highlighted option's value forwards or backwards, returning the option's
+    /// name and new value so that it can be saved.
+    pub fn change_selected_setting(&mut self, steps: i64) -> Option<(&'static str, SettingValue)> {
+        let setting = self.settings().into_iter().nth(self.settings_selected)?;
+        if let SettingKind::ReadOnly = setting.kind {
+            return None;
+        }
+        let value = setting.step(&self.config.get(setting.key)?, steps);
+        self.set_config_value(setting.key, value.clone())?;
+        Some((setting.key, value))
+    }
+
+    /// Changes a single config option for the rest of the session. Returns `None` if the
+    /// value isn't valid for the option.
+    pub fn set_config_value(&mut self, key: &str, value: SettingValue) -> Option<()> {
+        let config = self.config.with_value(key, value)?;
+        if matches!(key, "theme" | "light_theme" | "dark_theme") {
+            self.theme_name = config.resolved_theme(self.background).to_string();
+        }
+        self.color_depth = config.colors.resolve();
+        set_current_config(&config);
+        self.config = Rc::new(config);
+        // The value is saved to config.toml, but environment variables and CLI options still
+        // take precedence over it.
+        if self.config_source(key) == ConfigSource::Default {
+            self.config_sources.insert(key.to_string(), ConfigSource::File);
+        }
+        Some(())
+    }
+
+    pub fn open_command_palette(&mut self) {
+        self.command_palette = Some(CommandPalette {
+            filter: String::new(),
+            selected: 0,
+        });
+    }
+
+    /// The actions matching the command palette's filter, best matches first.
+    pub fn command_palette_matches(&self) -> Vec<PaletteAction> {
+        let filter = self
+            .command_palette
+            .as_ref()
+            .map_or("", |palette| palette.filter.as_str());
+        let actions = PaletteAction::all()
+            .into_iter()
+            .map(|action| (action.label(), action))
+            .collect();
+        fuzzy_filter(filter, actions, |(label, _)| label)
+            .into_iter()
+            .map(|(_, action)| action)
+            .collect()
+    }
+
+    /// Moves the command palette's selection by `delta`, wrapping around at either end.
+    pub fn move_command_palette_selection(&mut self, delta: isize) {
+        let num_matches = self.command_palette_matches().len() as isize;
+        if let Some(palette) = &mut self.command_palette
+            && num_matches > 0
+        {
+            palette.selected = (palette.selected as isize + delta).rem_euclid(num_matches) as usize;
+        }
+    }
+
+    pub fn set_command_palette_filter(&mut self, filter: String) {
+        if let Some(palette) = &mut self.command_palette {
+            palette.filter = filter;
+            palette.selected = 0;
+        }
+    }
+
+    /// Closes the command palette, returning the highlighted action if `confirm` is true.
+    pub fn close_command_palette(&mut self, confirm: bool) -> Option<PaletteAction> {
+        let matches = self.command_palette_matches();
+        let palette = self.command_palette.take()?;
+        if confirm {
+            matches.get(palette.selected).copied()
         } else {
+            None
+        }
+    }
+
+    /// The target wpm to use after toggling the ghost cursor on or off from the palette.
+    pub fn toggled_target_wpm(&self) -> usize {
+        if self.config.target_wpm > 0 {
             0
+        } else {
+            DEFAULT_GHOST_WPM
         }
     }
 
-    pub fn game_time_remaining_millis(&self) -> u64 {
-        (self.time_remaining.as_millis() as u64).saturating_sub(self.game_time_elapsed_millis())
+    /// Shows the results of the finished game: either the results screen or, if a fail
+    /// condition was met, the failed screen.
+    pub fn show_results(&mut self) {
+        self.load_results_screen_effect = load_score_screen_effect();
+        self.current_screen = match self.game.state() {
+            GameState::Finished(Some(reason)) => Screen::Failed(reason),
+            _ => Screen::Results,
+        };
     }
 
-    pub fn refresh_internal_score(&mut self) {
-        let mut character_matches: usize = 0;
-        let mut character_mismatches: usize = 0;
-        let mut num_correct_words: usize = 0;
-
-        // Count hits and misses
-        for (index, attempt) in self.words.iter().enumerate() {
-            let zipped_chars;
-            if index != self.current_word_offset {
-                zipped_chars = attempt.user_attempt.chars().zip(attempt.word.chars());
-            } else {
-                zipped_chars = self.current_user_input.chars().zip(attempt.word.chars());
-            }
-            let mut this_word_hits = 0;
-            for (user_char, expected_char) in zipped_chars {
-                let is_hit = user_char == expected_char;
-                if is_hit {
-                    character_matches += 1;
-                    this_word_hits += 1;
-                } else {
-                    character_mismatches += 1;
-                }
-            }
-            if this_word_hits == attempt.word.len() {
-                num_correct_words += 1
-            }
+    /// Records the line the cursor is currently on, restarting the memory mode timer if
+    /// the cursor has moved to a different line.
+    pub fn set_memory_row(&mut self, row: u16) {
+        if row != self.memory_row {
+            self.memory_row = row;
+            self.memory_row_shown_at = self.clock.now();
         }
+    }
 
-        let character_hits = self.score.character_hits;
-        let character_misses = self.score.character_misses;
-        let accuracy =
-            (character_hits as f32).div(character_hits.saturating_add(character_misses) as f32);
-
-        let num_chars = character_matches.saturating_add(character_mismatches);
-
-        // Chars and words per minute
-        let seconds_elapsed = (self.game_time_elapsed_millis() as f32) / 1000.;
-        let minutes_elapsed = seconds_elapsed / 60.;
-        let chars_per_minute = num_chars as f32 / minutes_elapsed;
-        let real_words_per_minute = num_correct_words as f32 / minutes_elapsed;
-        // We add the num_correct_words below as it represents the number of spaces, which should
-        // be included in the WPM calculation.
-        let mut wpm =
-            ((character_matches + num_correct_words) as f32 / 5.) * (60. / seconds_elapsed);
-
-        if wpm.is_infinite() {
-            wpm = 0.;
+    /// Whether the untyped words should currently be hidden in memory mode.
+    pub fn upcoming_words_hidden(&self) -> bool {
+        if self.config.memory == 0 || !self.game.is_active() {
+            return false;
         }
+        let started_at = self.game.started_at().unwrap_or_default();
+        let shown_since = max(self.memory_row_shown_at, started_at);
+        self.clock.now().saturating_sub(shown_since) >= Duration::from_secs(self.config.memory as u64)
+    }
+}
 
-        self.score = Score {
-            character_matches,
-            character_mismatches,
-            character_hits: self.score.character_hits,
-            character_misses: self.score.character_misses,
-            accuracy,
-            chars_per_minute,
-            wpm,
-            real_words_per_minute,
-            num_words: num_correct_words,
-            best_char_streak: self.score.best_char_streak,
-            current_char_streak: self.score.current_char_streak,
+/// Starts a new game with the rules and words from the config, or with the words of `text`.
+/// Text is always typed against the clock, as zen mode has no words to type.
+pub fn new_game(config: &Config, text: Option<&str>, clock: Rc<dyn Clock>) -> Game {
+    let game_config = GameConfig {
+        mode: if text.is_some() { Mode::Time } else { config.mode },
+        time: Duration::from_secs(config.time as u64),
+        sudden_death: config.sudden_death,
+        min_accuracy: config.min_accuracy,
+    };
+    let word_generator: Box<dyn WordGenerator> = match (text, config.mode) {
+        // The text has its own punctuation.
+        (Some(text), _) => Box::new(TextWords::new(text)),
+        (None, Mode::Time) if config.punctuation => {
+            let random_words = RandomWords::new(config.word_list.words());
+            Box::new(Punctuated::new(Box::new(random_words)))
         }
-    }
+        (None, Mode::Time) => Box::new(RandomWords::new(config.word_list.words())),
+        (None, Mode::Zen) => Box::new(NoWords),
+    };
+    Game::new(game_config, word_generator, clock)
 }
 
-fn generate_words() -> Vec<WordAttempt> {
-    let mut rng = rand::rng();
-    words::ENGLISH_1K_WORDS
-        .iter()
-        .choose_multiple(&mut rng, NUMBER_OF_WORDS_TO_PICK)
-        .iter()
-        .map(|s| WordAttempt::new(s.to_string()))
-        .collect()
+/// The theme used when colours are disabled, which relies on modifiers to show mistakes.
+fn monochrome_theme() -> Theme {
+    Theme {
+        name: "monochrome".to_string(),
+        fg: Color::Reset,
+        bg: Color::Reset,
+        primary: Color::Reset,
+        secondary: Color::Reset,
+        success: Color::Reset,
+        error: Color::Reset,
+        supports_alpha: false,
+        character_match: Style::default().bold().not_dim(),
+        character_mismatch: Style::default().reversed(),
+        character_upcoming: Color::Reset,
+    }
 }
 
-fn get_themes() -> Vec<Theme> {
+pub fn get_themes() -> Vec<Theme> {
     vec![
         Theme {
-            name: "terminal-yellow",
+            name: "terminal-yellow".to_string(),
             fg: Color::Reset,
             bg: Color::Reset,
             primary: Color::Yellow,
@@ -288,7 +580,7 @@ fn get_themes() -> Vec<Theme> {
             character_upcoming: Color::White,
         },
         Theme {
-            name: "terminal-cyan",
+            name: "terminal-cyan".to_string(),
             fg: Color::White,
             bg: Color::Blue,
             primary: Color::Cyan,
@@ -301,7 +593,7 @@ fn get_themes() -> Vec<Theme> {
             character_upcoming: Color::White,
         },
         Theme {
-            name: "nord",
+            name: "nord".to_string(),
             fg: Color::from_u32(0xD8DEE9),        // nord4
             bg: Color::from_u32(0x2E3440),        // nord0
             primary: Color::from_u32(0x88C0D0),   // nord8
@@ -314,7 +606,7 @@ fn get_themes() -> Vec<Theme> {
             character_upcoming: Color::from_u32(0xD8DEE9),
         },
         Theme {
-            name: "catppuccin-mocha",
+            name: "catppuccin-mocha".to_string(),
             fg: Color::from_u32(0xCDD6F4),        // Text
             bg: Color::from_u32(0x1E1E2E),        // Base
             primary: Color::from_u32(0x89B4FA),   // Blue
@@ -327,7 +619,7 @@ fn get_themes() -> Vec<Theme> {
             character_upcoming: Color::from_u32(0xCDD6F4),
         },
         Theme {
-            name: "dracula",
+            name: "dracula".to_string(),
             fg: Color::from_u32(0xF8F8F2),        // Foreground
             bg: Color::from_u32(0x282A36),        // Background
             primary: Color::from_u32(0xBD93F9),   // Purple
@@ -340,7 +632,7 @@ fn get_themes() -> Vec<Theme> {
             character_upcoming: Color::from_u32(0xF8F8F2),
         },
         Theme {
-            name: "gruvbox",
+            name: "gruvbox".to_string(),
             fg: Color::from_u32(0xEBDBB2),        // fg1
             bg: Color::from_u32(0x282828),        // bg0
             primary: Color::from_u32(0xFABD2F),   // yellow
@@ -353,7 +645,7 @@ fn get_themes() -> Vec<Theme> {
             character_upcoming: Color::from_u32(0xA89984),  // f4
         },
         Theme {
-            name: "solarized-dark",
+            name: "solarized-dark".to_string(),
             fg: Color::from_u32(0x839496),        // base0
             bg: Color::from_u32(0x002B36),        // base03
             primary: Color::from_u32(0x268BD2),   // blue
@@ -366,7 +658,7 @@ fn get_themes() -> Vec<Theme> {
             character_upcoming: Color::from_u32(0x839496),
         },
         Theme {
-            name: "tokyo-night",
+            name: "tokyo-night".to_string(),
             fg: Color::from_u32(0xC0CAF5),        // fg
             bg: Color::from_u32(0x1A1B26),        // bg
             primary: Color::from_u32(0x7AA2F7),   // blue
@@ -379,7 +671,7 @@ fn get_themes() -> Vec<Theme> {
             character_upcoming: Color::from_u32(0x6584C9),
         },
         Theme {
-            name: "monokai",
+            name: "monokai".to_string(),
             fg: Color::from_u32(0xF8F8F2),
             bg: Color::from_u32(0x272822),
             primary: Color::from_u32(0xF92672),   // pink
@@ -392,7 +684,7 @@ fn get_themes() -> Vec<Theme> {
             character_upcoming: Color::from_u32(0x999999),
         },
         Theme {
-            name: "galaxy",
+            name: "galaxy".to_string(),
             fg: Color::from_u32(0xC0CAF5),
             bg: Color::from_u32(0x0F0F1F),
             primary: Color::from_u32(0xC45AFF),
@@ -404,10 +696,98 @@ fn get_themes() -> Vec<Theme> {
             character_mismatch: Style::default().fg(Color::from_u32(0xFF4500)),
             character_upcoming: Color::from_u32(0xC0CAF5),
         },
+        Theme {
+            name: "solarized-light".to_string(),
+            fg: Color::from_u32(0x657B83),        // base00
+            bg: Color::from_u32(0xFDF6E3),        // base3
+            primary: Color::from_u32(0x268BD2),   // blue
+            secondary: Color::from_u32(0x2AA198), // cyan
+            success: Color::from_u32(0x859900),   // green
+            error: Color::from_u32(0xDC322F),     // red
+            supports_alpha: true,
+            character_match: Style::default().fg(Color::from_u32(0x859900)),
+            character_mismatch: Style::default().fg(Color::from_u32(0xDC322F)),
+            character_upcoming: Color::from_u32(0x93A1A1), // base1
+        },
+        Theme {
+            name: "catppuccin-latte".to_string(),
+            fg: Color::from_u32(0x4C4F69),        // Text
+            bg: Color::from_u32(0xEFF1F5),        // Base
+            primary: Color::from_u32(0x1E66F5),   // Blue
+            secondary: Color::from_u32(0x8839EF), // Mauve
+            success: Color::from_u32(0x40A02B),   // Green
+            error: Color::from_u32(0xD20F39),     // Red
+            supports_alpha: true,
+            character_match: Style::default().fg(Color::from_u32(0x40A02B)).not_dim(),
+            character_mismatch: Style::default().fg(Color::from_u32(0xD20F39)),
+            character_upcoming: Color::from_u32(0x6C6F85), // Subtext 0
+        },
+        Theme {
+            name: "github-light".to_string(),
+            fg: Color::from_u32(0x24292F),
+            bg: Color::from_u32(0xFFFFFF),
+            primary: Color::from_u32(0x0969DA),   // blue
+            secondary: Color::from_u32(0x8250DF), // purple
+            success: Color::from_u32(0x1A7F37),   // green
+            error: Color::from_u32(0xCF222E),     // red
+            supports_alpha: true,
+            character_match: Style::default().fg(Color::from_u32(0x1A7F37)).not_dim(),
+            character_mismatch: Style::default().fg(Color::from_u32(0xCF222E)),
+            character_upcoming: Color::from_u32(0x57606A),
+        },
+        Theme {
+            name: "gruvbox-light".to_string(),
+            fg: Color::from_u32(0x3C3836),        // fg1
+            bg: Color::from_u32(0xFBF1C7),        // bg0
+            primary: Color::from_u32(0xB57614),   // yellow
+            secondary: Color::from_u32(0x427B58), // aqua
+            success: Color::from_u32(0x79740E),   // green
+            error: Color::from_u32(0x9D0006),     // red
+            supports_alpha: true,
+            character_match: Style::default().fg(Color::from_u32(0x282828)).not_dim(),
+            character_mismatch: Style::default().fg(Color::from_u32(0x9D0006)),
+            character_upcoming: Color::from_u32(0x7C6F64), // f4
+        },
+        Theme {
+            name: "rose-pine-dawn".to_string(),
+            fg: Color::from_u32(0x575279),        // text
+            bg: Color::from_u32(0xFAF4ED),        // base
+            primary: Color::from_u32(0x286983),   // pine
+            secondary: Color::from_u32(0x907AA9), // iris
+            success: Color::from_u32(0x56949F),   // foam
+            error: Color::from_u32(0xB4637A),     // love
+            supports_alpha: true,
+            character_match: Style::default().fg(Color::from_u32(0x286983)).not_dim(),
+            character_mismatch: Style::default().fg(Color::from_u32(0xB4637A)),
+            character_upcoming: Color::from_u32(0x797593), // subtle
+        },
+        // The colourblind themes use the Okabe-Ito palette, which avoids relying on red and
+        // green so it can be used with deuteranopia and protanopia.
+        Theme {
+            name: "colorblind-dark".to_string(),
+            fg: Color::from_u32(0xE6E6E6),
+            bg: Color::from_u32(0x1C1C1C),
+            primary: Color::from_u32(0x56B4E9),   // sky blue
+            secondary: Color::from_u32(0xF0E442), // yellow
+            success: Color::from_u32(0x0072B2),   // blue
+            error: Color::from_u32(0xE69F00),     // orange
+            supports_alpha: true,
+            character_match: Style::default().fg(Color::from_u32(0x56B4E9)).not_dim(),
+            character_mismatch: Style::default().fg(Color::from_u32(0xE69F00)),
+            character_upcoming: Color::from_u32(0xA0A0A0),
+        },
+        Theme {
+            name: "colorblind-light".to_string(),
+            fg: Color::from_u32(0x1C1C1C),
+            bg: Color::from_u32(0xFAFAFA),
+            primary: Color::from_u32(0x0072B2),   // blue
+            secondary: Color::from_u32(0xCC79A7), // reddish purple
+            success: Color::from_u32(0x0072B2),   // blue
+            error: Color::from_u32(0xD55E00),     // vermillion
+            supports_alpha: true,
+            character_match: Style::default().fg(Color::from_u32(0x0072B2)).not_dim(),
+            character_mismatch: Style::default().fg(Color::from_u32(0xD55E00)),
+            character_upcoming: Color::from_u32(0x6E6E6E),
+        },
     ]
 }
-
-fn get_theme(theme_name: &str) -> Theme {
-    let themes = get_themes();
-    themes.iter().find(|t| t.name == theme_name).unwrap().clone()
-}
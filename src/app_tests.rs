@@ -0,0 +1,170 @@
+//! Tests for how the app handles key presses: changing settings, and the errors it shows in the
+//! footer instead of ending the session.
+
+use crate::app::{get_themes, App};
+use crate::config::{Config, ConfigSource, SettingKind, SettingValue};
+use crate::history::History;
+use crate::terminal::Background;
+use crate::{end_game_if_finished, handle_key};
+use o4t::clock::ManualClock;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+use std::{env, fs, process};
+
+struct Session {
+    app: App,
+    clock: ManualClock,
+    history: History,
+    dir: PathBuf,
+}
+
+impl Session {
+    fn new(name: &str, config: Config) -> Session {
+        let dir = env::temp_dir().join(format!("o4t-app-tests-{}-{}", process::id(), name));
+        fs::create_dir_all(&dir).unwrap();
+        let clock = ManualClock::new();
+        Session {
+            app: App::with_config(Rc::new(config), get_themes(), Rc::new(clock.clone())),
+            clock,
+            history: History::new(dir.join("history.jsonl")),
+            dir,
+        }
+    }
+
+    fn config_file(&self) -> PathBuf {
+        self.dir.join("config.toml")
+    }
+
+    fn press(&mut self, code: KeyCode) {
+        self.press_with(code, KeyModifiers::NONE);
+    }
+
+    fn press_with(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let key = KeyEvent::new(code, modifiers);
+        let config_file = self.config_file();
+        handle_key(&mut self.app, key, &self.history, &config_file).unwrap();
+    }
+
+    fn type_text(&mut self, text: &str) {
+        for char in text.chars() {
+            self.press(KeyCode::Char(char));
+        }
+    }
+
+    /// Moves time forward, ticking the game like the main loop does.
+    fn advance(&mut self, duration: Duration) {
+        self.clock.advance(duration);
+        self.app.game.tick();
+        end_game_if_finished(&mut self.app, &self.history);
+    }
+
+    fn error(&self) -> &str {
+        self.app.error.as_deref().unwrap_or_default()
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[test]
+fn history_errors_are_shown_without_ending_the_session() {
+    let config = Config {
+        time: 15,
+        ..Config::default()
+    };
+    let mut session = Session::new("history-error", config);
+    // The history file can't be written when there's a directory in the way.
+    session.history = History::new(session.dir.clone());
+    session.type_text("the ");
+    session.advance(Duration::from_secs(15));
+    assert!(session.error().starts_with("couldn't save the game to the history"));
+    assert!(session.app.last_game.is_some());
+
+    // The next key press clears the error, and the game carries on.
+    session.press(KeyCode::Tab);
+    assert_eq!(session.app.error, None);
+}
+
+#[test]
+fn config_errors_are_shown_without_ending_the_session() {
+    let mut session = Session::new("config-error", Config::default());
+    // config.toml can't be written when there's a directory in the way.
+    fs::create_dir_all(session.config_file()).unwrap();
+    session.press_with(KeyCode::Char('t'), KeyModifiers::CONTROL);
+    session.type_text("nord");
+    session.press(KeyCode::Enter);
+    assert_eq!(session.app.theme_name, "nord");
+    assert!(session.error().starts_with("couldn't save theme to"), "{}", session.error());
+}
+
+#[test]
+fn command_palette_errors_are_shown_without_ending_the_session() {
+    let mut session = Session::new("palette-error", Config::default());
+    session.history = History::new(session.dir.clone());
+    fs::create_dir_all(session.config_file()).unwrap();
+    session.press_with(KeyCode::Char('p'), KeyModifiers::CONTROL);
+    session.type_text("open stats");
+    session.press(KeyCode::Enter);
+    assert!(session.error().starts_with("couldn't read the history"), "{}", session.error());
+
+    session.press_with(KeyCode::Char('p'), KeyModifiers::CONTROL);
+    session.type_text("punctuation");
+    session.press(KeyCode::Enter);
+    assert!(session.app.config.punctuation);
+    assert!(session.error().starts_with("couldn't save punctuation to"), "{}", session.error());
+}
+
+#[test]
+fn settings_overridden_by_the_environment_keep_their_source() {
+    let mut session = Session::new("settings-env", Config::default());
+    session.app.config_sources.insert("time".to_string(), ConfigSource::Env);
+    session.press_with(KeyCode::Char('s'), KeyModifiers::CONTROL);
+    session.press(KeyCode::Down);
+    session.press(KeyCode::Right);
+    assert_eq!(session.app.config.time, 35);
+    assert_eq!(session.app.config_source("time"), ConfigSource::Env);
+    assert_eq!(session.error(), "time was saved, but O4T_TIME overrides it");
+    let saved = fs::read_to_string(session.config_file()).unwrap();
+    assert_eq!(saved.trim(), "time = 35");
+
+    session.press(KeyCode::Down);
+    session.press(KeyCode::Right);
+    assert_eq!(session.app.config_source("word_list"), ConfigSource::File);
+}
+
+#[test]
+fn key_bindings_are_shown_but_not_edited_on_the_settings_screen() {
+    let mut session = Session::new("settings-keybindings", Config::default());
+    session.press_with(KeyCode::Char('s'), KeyModifiers::CONTROL);
+    // The selection wraps around to the last option.
+    session.press(KeyCode::Up);
+    session.press(KeyCode::Right);
+    let setting = session.app.settings().pop().unwrap();
+    assert_eq!(setting.key, "keybindings");
+    assert!(matches!(setting.kind, SettingKind::ReadOnly));
+    assert_eq!(setting.value_label(&session.app.config), "edit config.toml");
+    assert!(!session.config_file().exists());
+}
+
+#[test]
+fn auto_theme_is_only_resolved_for_display() {
+    let config = Config {
+        theme: "auto".to_string(),
+        ..Config::default()
+    };
+    let clock = Rc::new(ManualClock::new());
+    let mut app = App::with_config(Rc::new(config), get_themes(), clock)
+        .with_background(Some(Background::Light));
+    assert_eq!(app.theme_name, "catppuccin-latte");
+
+    app.set_config_value("light_theme", SettingValue::Text("github-light".to_string()));
+    app.reset_game();
+    assert_eq!(app.theme_name, "github-light");
+    // The config keeps "auto", so it's what gets saved and shown on the settings screen.
+    assert_eq!(app.config.theme, "auto");
+}
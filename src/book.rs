@@ -0,0 +1,321 @@
+//! Book mode, for typing through a long document over many sessions. Each game is a page of
+//! the book, and the position and per-chapter stats are saved in the data directory after
+//! every game, so the next session carries on where the last one stopped.
+
+use o4t::game::{Game, GameState};
+use o4t::generator::normalize_text;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, create_dir_all};
+use std::io;
+use std::path::{Path, PathBuf};
+
+// The most words in a page. Pages start at the beginning of a chapter, so the last page of a
+// chapter may be shorter.
+const PAGE_WORDS: usize = 100;
+
+/// A chapter of a book, which starts at a heading line.
+struct Chapter {
+    title: String,
+    // The index of the chapter's first word, and the number of words in it.
+    start: usize,
+    len: usize,
+}
+
+/// How much of a chapter has been typed, and how well.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+struct ChapterStats {
+    games: usize,
+    words: usize,
+    // Time spent typing the chapter, in seconds.
+    seconds: f32,
+    // Correctly typed characters, including the spaces after correct words, as used for wpm.
+    correct_chars: usize,
+    character_hits: usize,
+    character_misses: usize,
+}
+
+impl ChapterStats {
+    // Both are 0 rather than NaN or infinite for games that were over as soon as they started.
+    fn wpm(&self) -> f32 {
+        if self.seconds == 0. {
+            return 0.;
+        }
+        (self.correct_chars as f32 / 5.) / (self.seconds / 60.)
+    }
+
+    fn accuracy(&self) -> f32 {
+        let typed = self.character_hits + self.character_misses;
+        if typed == 0 {
+            return 0.;
+        }
+        self.character_hits as f32 / typed as f32
+    }
+}
+
+/// What's saved between sessions.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Progress {
+    // The index of the next word to type.
+    position: usize,
+    // The number of times the whole book has been typed, after which it starts again.
+    times_finished: usize,
+    // Indexed in the same order as Book::chapters.
+    chapters: Vec<ChapterStats>,
+}
+
+pub struct Book {
+    pub title: String,
+    words: Vec<String>,
+    chapters: Vec<Chapter>,
+    progress: Progress,
+    // Where progress is saved. The file is named after a hash of the words, so the progress
+    // is kept if the book is moved, but not if its text changes.
+    progress_path: PathBuf,
+}
+
+impl Book {
+    /// Reads a plain text book, and the progress through it from `books_dir`. Headings (see
+    /// `is_heading`) start a new chapter, and aren't typed.
+    pub fn open(file: &Path, ascii: bool, books_dir: &Path) -> io::Result<Book> {
+        let bytes = fs::read(file)?;
+        let title = file
+            .file_stem()
+            .map_or_else(|| file.display().to_string(), |stem| stem.to_string_lossy().into());
+        let (words, chapters) = split_chapters(&String::from_utf8_lossy(&bytes), &title, ascii);
+        if words.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "there are no words in it"));
+        }
+
+        let progress_path = books_dir.join(format!("{:016x}.json", hash_words(&words)));
+        let progress_error = |err: &dyn std::fmt::Display| {
+            let message = format!(
+                "couldn't read progress from {} (delete it to start the book over): {}",
+                progress_path.display(),
+                err
+            );
+            io::Error::new(io::ErrorKind::InvalidData, message)
+        };
+        let mut progress = match fs::read_to_string(&progress_path) {
+            Ok(json) => {
+                serde_json::from_str::<Progress>(&json).map_err(|err| progress_error(&err))?
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Progress::default(),
+            Err(err) => return Err(progress_error(&err)),
+        };
+        progress.chapters.resize(chapters.len(), ChapterStats::default());
+        if progress.position >= words.len() {
+            progress.position = 0;
+        }
+        Ok(Book {
+            title,
+            words,
+            chapters,
+            progress,
+            progress_path,
+        })
+    }
+
+    /// Goes back to the start of the book, keeping the stats.
+    pub fn restart(&mut self) -> io::Result<()> {
+        self.progress.position = 0;
+        self.save()
+    }
+
+    /// The words of the current page, from where the user got up to, as text for a game.
+    pub fn page_text(&self) -> String {
+        self.words[self.progress.position..self.page_end()].join(" ")
+    }
+
+    /// Where the user is up to, e.g. "Chapter 2 · page 3/12 · 14%".
+    pub fn location(&self) -> String {
+        let chapter = &self.chapters[self.current_chapter()];
+        format!(
+            "{} · page {}/{} · {:.0}%",
+            chapter.title,
+            (self.progress.position - chapter.start) / PAGE_WORDS + 1,
+            chapter.len.div_ceil(PAGE_WORDS),
+            self.progress.position as f32 / self.words.len() as f32 * 100.
+        )
+    }
+
+    /// Moves past the words typed in a game of the current page, adds the game to the
+    /// chapter's stats, and saves the progress. The game has either finished, or is being
+    /// quit part way through. Only the words typed correctly before the first mistake (or
+    /// skipped word) count, so the next game starts again from there.
+    pub fn record_game(&mut self, game: &Game) -> io::Result<()> {
+        if game.state() == GameState::Waiting {
+            return Ok(());
+        }
+        let page_len = self.page_end() - self.progress.position;
+        let words = game.words()[..game.current_word_offset().min(page_len)]
+            .iter()
+            .take_while(|attempt| attempt.user_attempt == attempt.word)
+            .count();
+        let score = game.score();
+        let chapter = self.current_chapter();
+        let stats = &mut self.progress.chapters[chapter];
+        stats.games += 1;
+        stats.words += words;
+        stats.seconds += game.elapsed().as_secs_f32();
+        stats.correct_chars += score.character_matches + score.num_words;
+        stats.character_hits += score.character_hits;
+        stats.character_misses += score.character_misses;
+
+        self.progress.position += words;
+        if self.progress.position >= self.words.len() {
+            self.progress.position = 0;
+            self.progress.times_finished += 1;
+        }
+        self.save()
+    }
+
+    /// A table of each chapter's progress and stats, for `o4t book --stats`.
+    pub fn stats_table(&self) -> String {
+        let title_width = self
+            .chapters
+            .iter()
+            .map(|chapter| chapter.title.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut summary = format!(
+            "{}: {:.0}% ({} of {} words)",
+            self.title,
+            self.progress.position as f32 / self.words.len() as f32 * 100.,
+            self.progress.position,
+            self.words.len()
+        );
+        match self.progress.times_finished {
+            0 => {}
+            1 => summary += ", finished once",
+            times => summary += &format!(", finished {} times", times),
+        }
+        let mut lines = vec![summary];
+        lines.push(format!(
+            "{:<title_width$}  {:>5}  {:>5}  {:>4}  {:>8}",
+            "chapter", "done", "games", "wpm", "accuracy"
+        ));
+        for (index, chapter) in self.chapters.iter().enumerate() {
+            let stats = &self.progress.chapters[index];
+            let done = self
+                .progress
+                .position
+                .saturating_sub(chapter.start)
+                .min(chapter.len);
+            let (wpm, accuracy) = if stats.games == 0 {
+                ("-".to_string(), "-".to_string())
+            } else {
+                (
+                    format!("{:.0}", stats.wpm()),
+                    format!("{:.0}%", stats.accuracy() * 100.),
+                )
+            };
+            lines.push(format!(
+                "{:<title_width$}  {:>4.0}%  {:>5}  {:>4}  {:>8}",
+                chapter.title,
+                done as f32 / chapter.len as f32 * 100.,
+                stats.games,
+                wpm,
+                accuracy
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn current_chapter(&self) -> usize {
+        self.chapters
+            .iter()
+            .rposition(|chapter| chapter.start <= self.progress.position)
+            .unwrap_or(0)
+    }
+
+    fn page_end(&self) -> usize {
+        let chapter = &self.chapters[self.current_chapter()];
+        let page = (self.progress.position - chapter.start) / PAGE_WORDS;
+        (chapter.start + (page + 1) * PAGE_WORDS).min(chapter.start + chapter.len)
+    }
+
+    fn save(&self) -> io::Result<()> {
+        if let Some(parent) = self.progress_path.parent() {
+            create_dir_all(parent)?;
+        }
+        fs::write(&self.progress_path, serde_json::to_string(&self.progress)?)
+    }
+}
+
+// The most words in a "Chapter ..." heading, including its title.
+const HEADING_WORDS: usize = 10;
+
+// Number words used in chapter headings like "Chapter Twenty-One".
+const NUMBER_WORDS: [&str; 28] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven",
+    "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety", "hundred",
+];
+
+// Headings are Markdown-style "# Title" lines, and short lines like "Chapter 12", "CHAPTER IV."
+// or "Chapter One: Loomings". A title after the number has to be set apart with punctuation,
+// so prose like "Chapter I read was dull" isn't taken for a heading.
+fn is_heading(line: &str) -> bool {
+    let line = line.trim();
+    if line.starts_with('#') {
+        return true;
+    }
+    let words = line.split_whitespace().collect::<Vec<_>>();
+    let [first, number, rest @ ..] = words.as_slice() else {
+        return false;
+    };
+    let separated = number.ends_with([':', '.'])
+        || rest
+            .first()
+            .is_some_and(|word| matches!(*word, "-" | "\u{2013}" | "\u{2014}"));
+    let number = number.trim_end_matches([':', '.']).to_lowercase();
+    let is_number = number.chars().all(|char| char.is_ascii_digit())
+        || number.chars().all(|char| "ivxlcdm".contains(char))
+        || number.split('-').all(|part| NUMBER_WORDS.contains(&part));
+    first.eq_ignore_ascii_case("chapter")
+        && !number.is_empty()
+        && is_number
+        && (rest.is_empty() || separated)
+        && words.len() <= HEADING_WORDS
+}
+
+// Splits the text into its words and chapters. Text before the first heading is a chapter
+// named after the book, and chapters without any words (e.g. a part heading, followed by a
+// chapter heading) are left out.
+fn split_chapters(text: &str, title: &str, ascii: bool) -> (Vec<String>, Vec<Chapter>) {
+    let mut words = Vec::new();
+    let mut chapters = vec![Chapter {
+        title: title.to_string(),
+        start: 0,
+        len: 0,
+    }];
+    for line in text.lines() {
+        if is_heading(line) {
+            let title = normalize_text(line.trim_start().trim_start_matches('#'), ascii);
+            chapters.push(Chapter {
+                title,
+                start: words.len(),
+                len: 0,
+            });
+            continue;
+        }
+        let line = normalize_text(line, ascii);
+        let chapter = chapters.last_mut().expect("there's always a chapter");
+        for word in line.split_whitespace() {
+            words.push(word.to_string());
+            chapter.len += 1;
+        }
+    }
+    chapters.retain(|chapter| chapter.len > 0);
+    (words, chapters)
+}
+
+// FNV-1a, which unlike the standard library's hasher is stable across versions of Rust.
+fn hash_words(words: &[String]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in words.iter().flat_map(|word| word.bytes().chain([b' '])) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
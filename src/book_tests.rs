@@ -0,0 +1,216 @@
+//! Tests for book mode: splitting a document into chapters and pages, and keeping track of
+//! progress through it between sessions.
+
+use crate::app::{get_themes, new_game, App};
+use crate::book::Book;
+use crate::config::Config;
+use crate::terminal::ColorDepth;
+use crate::ui::ui;
+use o4t::clock::ManualClock;
+use o4t::game::{Game, InputEvent};
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+use std::{env, fs, process};
+
+// A directory for the book and its progress, which is emptied first.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("o4t-book-tests-{}-{}", process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Types the first `words` words of the book's current page, then lets the time run out.
+fn play_page(book: &mut Book, words: usize) -> Game {
+    let clock = ManualClock::new();
+    let config = Config::default();
+    let page = book.page_text();
+    let mut game = new_game(&config, Some(&page), Rc::new(clock.clone()));
+    for word in page.split(' ').take(words) {
+        for char in word.chars().chain([' ']) {
+            game.handle_input(InputEvent::Char(char));
+        }
+    }
+    clock.advance(Duration::from_secs(config.time as u64));
+    game.tick();
+    book.record_game(&game).unwrap();
+    game
+}
+
+#[test]
+fn chapters_are_split_into_pages() {
+    let dir = test_dir("pages");
+    let chapter = (0..150).map(|i| format!("w{}", i)).collect::<Vec<_>>().join(" ");
+    let file = dir.join("novel.txt");
+    let text = format!("# One\n{}\n\nChapter Two\n\n\u{201c}Hi.\u{201d}\n", chapter);
+    fs::write(&file, text).unwrap();
+
+    let mut book = Book::open(&file, true, &dir.join("books")).unwrap();
+    assert_eq!(book.location(), "One · page 1/2 · 0%");
+    assert_eq!(book.page_text().split(' ').count(), 100);
+
+    // Running out of time part way through a page carries on from the same place.
+    play_page(&mut book, 30);
+    assert_eq!(book.location(), "One · page 1/2 · 20%");
+    assert!(book.page_text().starts_with("w30 w31"));
+    assert_eq!(book.page_text().split(' ').count(), 70);
+
+    play_page(&mut book, 70);
+    play_page(&mut book, 50);
+    assert_eq!(book.location(), "Chapter Two · page 1/1 · 99%");
+    assert_eq!(book.page_text(), "\"Hi.\"");
+}
+
+#[test]
+fn progress_is_kept_between_sessions() {
+    let dir = test_dir("progress");
+    let file = dir.join("short.txt");
+    fs::write(&file, "Chapter 1\nthe cat sat\nChapter 2\non the mat\n").unwrap();
+
+    let mut book = Book::open(&file, false, &dir.join("books")).unwrap();
+    play_page(&mut book, 3);
+    let book = Book::open(&file, false, &dir.join("books")).unwrap();
+    assert_eq!(book.page_text(), "on the mat");
+    let stats = book.stats_table();
+    assert!(stats.starts_with("short: 50% (3 of 6 words)\n"), "{}", stats);
+    assert!(stats.contains("Chapter 1   100%      1"), "{}", stats);
+    assert!(stats.contains("Chapter 2     0%      0     -         -"), "{}", stats);
+
+    // Finishing the book starts it again.
+    let mut book = book;
+    play_page(&mut book, 3);
+    assert_eq!(book.page_text(), "the cat sat");
+    assert!(book.stats_table().contains("finished once"));
+}
+
+#[test]
+fn only_short_chapter_lines_are_headings() {
+    let dir = test_dir("headings");
+    let file = dir.join("headings.txt");
+    let text = "CHAPTER IV.\n\
+                Chapter and verse.\n\
+                Chapter 3: The Long Way Round\n\
+                Chapter I read was dull, she said.\n\
+                Chapter Twenty-One\n\
+                the end\n";
+    fs::write(&file, text).unwrap();
+
+    let book = Book::open(&file, false, &dir.join("books")).unwrap();
+    let stats = book.stats_table();
+    let titles = stats
+        .lines()
+        .skip(2)
+        .map(|line| line.split("  ").next().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(titles, ["CHAPTER IV.", "Chapter 3: The Long Way Round", "Chapter Twenty-One"]);
+    assert_eq!(book.page_text(), "Chapter and verse.");
+}
+
+#[test]
+fn quitting_part_way_through_a_page_keeps_the_words_typed() {
+    let dir = test_dir("quit");
+    let file = dir.join("quit.txt");
+    fs::write(&file, "one two three four five").unwrap();
+
+    let mut book = Book::open(&file, false, &dir.join("books")).unwrap();
+    let clock = ManualClock::new();
+    let config = Config::default();
+    let mut game = new_game(&config, Some(&book.page_text()), Rc::new(clock.clone()));
+    for char in "one two th".chars() {
+        game.handle_input(InputEvent::Char(char));
+    }
+    clock.advance(Duration::from_secs(5));
+    assert!(game.is_active());
+    book.record_game(&game).unwrap();
+
+    let book = Book::open(&file, false, &dir.join("books")).unwrap();
+    assert_eq!(book.page_text(), "three four five");
+    assert!(book.stats_table().contains("40%      1"), "{}", book.stats_table());
+}
+
+#[test]
+fn pages_with_typographic_punctuation_can_be_raced() {
+    let dir = test_dir("unicode");
+    let file = dir.join("unicode.txt");
+    let text = "\u{201c}Caf\u{e9} au lait,\u{201d} she said \u{2014} na\u{ef}vely.\n";
+    fs::write(&file, text).unwrap();
+
+    let book = Book::open(&file, false, &dir.join("books")).unwrap();
+    let clock = ManualClock::new();
+    let config = Config {
+        target_wpm: 60,
+        colors: ColorDepth::Truecolor,
+        ..Config::default()
+    };
+    let mut app = App::with_config(Rc::new(config), get_themes(), Rc::new(clock.clone()))
+        .with_book(book);
+    let mut terminal = Terminal::new(TestBackend::new(60, 14)).unwrap();
+    app.game.handle_input(InputEvent::Char('\u{201c}'));
+    // Move the ghost cursor through every character of the page, including the multi-byte ones.
+    for _ in 0..60 {
+        clock.advance(Duration::from_millis(200));
+        app.game.tick();
+        terminal.draw(|frame| ui(frame, &mut app)).unwrap();
+    }
+}
+
+#[test]
+fn only_words_typed_correctly_move_the_book_on() {
+    let dir = test_dir("mistakes");
+    let file = dir.join("mistakes.txt");
+    fs::write(&file, "one two three four five").unwrap();
+    let mut book = Book::open(&file, false, &dir.join("books")).unwrap();
+
+    // Skipping words by pressing space early doesn't count.
+    let clock = ManualClock::new();
+    let mut game = new_game(&Config::default(), Some(&book.page_text()), Rc::new(clock.clone()));
+    for char in "o t t f ".chars() {
+        game.handle_input(InputEvent::Char(char));
+    }
+    book.record_game(&game).unwrap();
+    assert_eq!(book.page_text(), "one two three four five");
+
+    // Nor do the words after a mistake.
+    let mut game = new_game(&Config::default(), Some(&book.page_text()), Rc::new(clock.clone()));
+    for char in "one tow three ".chars() {
+        game.handle_input(InputEvent::Char(char));
+    }
+    book.record_game(&game).unwrap();
+    assert_eq!(book.page_text(), "two three four five");
+}
+
+#[test]
+fn games_with_no_time_have_no_wpm() {
+    let dir = test_dir("instant");
+    let file = dir.join("instant.txt");
+    fs::write(&file, "one two").unwrap();
+    let mut book = Book::open(&file, false, &dir.join("books")).unwrap();
+
+    // The clock never moves, so the game takes no time at all.
+    let clock = ManualClock::new();
+    let mut game = new_game(&Config::default(), Some(&book.page_text()), Rc::new(clock));
+    for char in "one ".chars() {
+        game.handle_input(InputEvent::Char(char));
+    }
+    book.record_game(&game).unwrap();
+    let stats = book.stats_table();
+    assert!(stats.ends_with("instant    50%      1     0      100%"), "{}", stats);
+}
+
+#[test]
+fn corrupt_progress_files_are_named_in_the_error() {
+    let dir = test_dir("corrupt");
+    let file = dir.join("corrupt.txt");
+    fs::write(&file, "one two").unwrap();
+    let books_dir = dir.join("books");
+    Book::open(&file, false, &books_dir).unwrap().restart().unwrap();
+    let progress_file = fs::read_dir(&books_dir).unwrap().next().unwrap().unwrap().path();
+    fs::write(&progress_file, "{ not json").unwrap();
+
+    let err = Book::open(&file, false, &books_dir).err().unwrap().to_string();
+    let expected = format!("couldn't read progress from {}", progress_file.display());
+    assert!(err.starts_with(&expected), "{}", err);
+}
@@ -1,32 +1,163 @@
-use crate::app::{CurrentWord, CursorType};
-use clap::Parser;
+use crate::app::{CurrentWord, CursorType, ErrorIndicator};
+use crate::output::OutputFormat;
+use crate::terminal::ColorDepth;
+use o4t::game::{Mode, WordList};
+use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 #[derive(Parser, Debug, Serialize, Deserialize, Clone)]
 #[command(version, about)]
 pub struct Cli {
+    #[command(subcommand)]
+    #[serde(skip)]
+    pub command: Option<Command>,
 
-    #[clap(short, long, value_parser, value_name = "SECS")]
+    #[clap(short, long, value_enum, global = true)]
+    #[serde(skip_serializing_if = "::std::option::Option::is_none")]
+    pub mode: Option<Mode>,
+
+    #[clap(short, long, value_parser, value_name = "SECS", global = true)]
     #[serde(skip_serializing_if = "::std::option::Option::is_none")]
     pub time: Option<usize>,
 
+    #[clap(short, long, value_enum, global = true)]
+    #[serde(skip_serializing_if = "::std::option::Option::is_none")]
+    pub word_list: Option<WordList>,
+
+    #[clap(short, long, num_args = 0..=1, default_missing_value = "true", global = true)]
+    #[serde(skip_serializing_if = "::std::option::Option::is_none")]
+    pub punctuation: Option<bool>,
+
     #[clap(
         long,
         value_parser,
         value_name = "THEME_NAME",
+        global = true,
     )]
     #[serde(skip_serializing_if = "::std::option::Option::is_none")]
     pub theme: Option<String>,
 
-    #[clap(long, value_parser)]
+    #[clap(long, value_parser, value_name = "THEME_NAME", global = true)]
+    #[serde(skip_serializing_if = "::std::option::Option::is_none")]
+    pub light_theme: Option<String>,
+
+    #[clap(long, value_parser, value_name = "THEME_NAME", global = true)]
+    #[serde(skip_serializing_if = "::std::option::Option::is_none")]
+    pub dark_theme: Option<String>,
+
+    #[clap(long, value_parser, global = true)]
     #[serde(skip_serializing_if = "::std::option::Option::is_none")]
     pub target_wpm: Option<usize>,
     
-    #[clap(short, long, value_enum, value_name = "STYLE")]
+    #[clap(short, long, value_enum, value_name = "STYLE", global = true)]
     #[serde(skip_serializing_if = "::std::option::Option::is_none")]
     pub cursor: Option<CursorType>,
     
-    #[clap(long, value_enum, value_name = "FOCUS_STYLE")]
+    #[clap(long, value_enum, value_name = "FOCUS_STYLE", global = true)]
     #[serde(skip_serializing_if = "::std::option::Option::is_none")]
     pub current_word: Option<CurrentWord>,
+
+    #[clap(long, value_enum, value_name = "INDICATOR", global = true)]
+    #[serde(skip_serializing_if = "::std::option::Option::is_none")]
+    pub error_indicator: Option<ErrorIndicator>,
+
+    #[clap(long, num_args = 0..=1, default_missing_value = "true", global = true)]
+    #[serde(skip_serializing_if = "::std::option::Option::is_none")]
+    pub sudden_death: Option<bool>,
+
+    #[clap(
+        long,
+        value_parser = clap::value_parser!(u8).range(0..=100),
+        value_name = "PERCENT",
+        global = true,
+    )]
+    #[serde(skip_serializing_if = "::std::option::Option::is_none")]
+    pub min_accuracy: Option<u8>,
+
+    #[clap(long, num_args = 0..=1, default_missing_value = "true", global = true)]
+    #[serde(skip_serializing_if = "::std::option::Option::is_none")]
+    pub blind: Option<bool>,
+
+    #[clap(long, value_parser, value_name = "SECS", global = true)]
+    #[serde(skip_serializing_if = "::std::option::Option::is_none")]
+    pub memory: Option<usize>,
+
+    #[clap(long, value_enum, value_name = "DEPTH", global = true)]
+    #[serde(skip_serializing_if = "::std::option::Option::is_none")]
+    pub colors: Option<ColorDepth>,
+
+    // Only used for this run, so it isn't part of the config.
+    #[clap(long, value_enum, value_name = "FORMAT", global = true)]
+    #[serde(skip)]
+    pub output: Option<OutputFormat>,
+
+    // The text to type instead of generated words. "-" reads it from stdin, which is also
+    // read when text is piped in without this option.
+    #[clap(long, value_name = "FILE", global = true)]
+    #[serde(skip)]
+    pub text: Option<PathBuf>,
+
+    // Replaces smart quotes, dashes and ellipses in the text with plain ASCII.
+    #[clap(long, global = true)]
+    #[serde(skip)]
+    pub ascii: bool,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Manage themes
+    Theme {
+        #[command(subcommand)]
+        command: ThemeCommand,
+    },
+    /// List the available themes, with a preview of each
+    Themes,
+    /// Start a game, like running o4t on its own, and exit with a non-zero code if the last
+    /// game failed or missed a target
+    Run {
+        /// Exit automatically after the first game's results have been shown
+        #[clap(long)]
+        once: bool,
+
+        /// The lowest wpm that counts as a pass
+        #[clap(long, value_name = "WPM")]
+        min_wpm: Option<f32>,
+
+        /// The lowest accuracy that counts as a pass. Unlike --min-accuracy, which fails the
+        /// game as soon as accuracy drops below it, this is only checked once the game is over
+        #[clap(long, value_parser = clap::value_parser!(u8).range(0..=100), value_name = "PERCENT")]
+        target_accuracy: Option<u8>,
+    },
+    /// Type through a long plain text document a page at a time, carrying on from where you
+    /// left off last time
+    Book {
+        /// The document to type. Lines starting with "#" or "Chapter" start a new chapter
+        file: PathBuf,
+
+        /// Print how far through each chapter you are, and your stats for it, then exit
+        #[clap(long)]
+        stats: bool,
+
+        /// Go back to the start of the book. Your stats are kept
+        #[clap(long)]
+        restart: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ThemeCommand {
+    /// Convert a base16 YAML, Alacritty TOML or Kitty colour scheme into an o4t theme file
+    Import {
+        /// The colour scheme file to import
+        file: PathBuf,
+
+        /// The name of the new theme (defaults to the name of the colour scheme)
+        #[clap(long)]
+        name: Option<String>,
+
+        /// Overwrite an existing theme with the same name
+        #[clap(long)]
+        force: bool,
+    },
 }
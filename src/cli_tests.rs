@@ -0,0 +1,32 @@
+//! Tests for parsing the command line.
+
+use crate::cli::{Cli, Command};
+use clap::{CommandFactory, Parser};
+
+#[test]
+fn the_command_line_is_well_formed() {
+    Cli::command().debug_assert();
+}
+
+#[test]
+fn game_options_can_go_after_the_subcommand() {
+    let cli = Cli::try_parse_from(["o4t", "run", "--once", "--time", "15", "-p"]).unwrap();
+    assert_eq!(cli.time, Some(15));
+    assert_eq!(cli.punctuation, Some(true));
+    assert!(matches!(cli.command, Some(Command::Run { once: true, .. })));
+
+    let cli = Cli::try_parse_from(["o4t", "--time", "15", "run", "--once"]).unwrap();
+    assert_eq!(cli.time, Some(15));
+}
+
+#[test]
+fn run_targets_are_separate_from_game_options() {
+    let args = ["o4t", "run", "--min-accuracy", "80", "--target-accuracy", "95", "--min-wpm", "60"];
+    let cli = Cli::try_parse_from(args).unwrap();
+    assert_eq!(cli.min_accuracy, Some(80));
+    let Some(Command::Run { min_wpm, target_accuracy, .. }) = cli.command else {
+        panic!("expected the run command, got {:?}", cli.command);
+    };
+    assert_eq!(min_wpm, Some(60.));
+    assert_eq!(target_accuracy, Some(95));
+}
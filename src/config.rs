@@ -1,23 +1,290 @@
-use crate::app::{CurrentWord, CursorType};
-use serde::{Deserialize, Serialize};
+use crate::app::{CurrentWord, CursorType, ErrorIndicator};
+use crate::keybindings::KeyBindings;
+use crate::terminal::{Background, ColorDepth};
+use o4t::game::{Mode, WordList};
+use clap::ValueEnum;
+use figment::providers::Serialized;
+use figment::Figment;
+use serde::de::{Error, Unexpected};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::path::Path;
+use std::{fs, io};
+use toml_edit::{DocumentMut, Value};
+
+// When used as the theme name, the theme is picked based on the terminal's background colour.
+pub const AUTO_THEME: &str = "auto";
 
 #[derive(Serialize, Deserialize)]
 pub struct Config {
+    pub mode: Mode,
     pub time: usize,
+    pub word_list: WordList,
+    // Adds capital letters and punctuation to the generated words.
+    pub punctuation: bool,
     pub theme: String,
+    // The themes used when `theme` is "auto", depending on the terminal's background colour.
+    pub light_theme: String,
+    pub dark_theme: String,
     pub target_wpm: usize,
     pub cursor: CursorType,
     pub current_word: CurrentWord,
+    pub error_indicator: ErrorIndicator,
+    pub sudden_death: bool,
+    #[serde(deserialize_with = "deserialize_percentage")]
+    pub min_accuracy: usize,
+    pub blind: bool,
+    pub memory: usize,
+    pub colors: ColorDepth,
+    pub keybindings: KeyBindings,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            mode: Mode::Time,
             time: 30,
+            word_list: WordList::English1k,
+            punctuation: false,
             theme: "dracula".to_string(),
+            light_theme: "catppuccin-latte".to_string(),
+            dark_theme: "dracula".to_string(),
             target_wpm: 0,
             cursor: CursorType::Underline,
             current_word: CurrentWord::Highlight,
+            error_indicator: ErrorIndicator::None,
+            sudden_death: false,
+            min_accuracy: 0,
+            blind: false,
+            memory: 0,
+            colors: ColorDepth::Auto,
+            keybindings: KeyBindings::default(),
         }
     }
 }
+
+// Rejects percentages over 100 from the config file or environment, like the command line
+// does, rather than failing every game on the first key press.
+fn deserialize_percentage<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    let value = usize::deserialize(deserializer)?;
+    if value > 100 {
+        let unexpected = Unexpected::Unsigned(value as u64);
+        return Err(D::Error::invalid_value(unexpected, &"a percentage from 0 to 100"));
+    }
+    Ok(value)
+}
+
+/// Sets a single top-level value in the config file, leaving the rest of the file
+/// (including comments and formatting) untouched. Creates the file if it doesn't exist.
+pub fn save_config_value(path: &Path, key: &str, value: impl Into<Value>) -> io::Result<()> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    let mut document = contents
+        .parse::<DocumentMut>()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+    let mut value = value.into();
+    match document.get_mut(key).and_then(|item| item.as_value_mut()) {
+        Some(existing) => {
+            // Keep any comment on the same line as the existing value.
+            *value.decor_mut() = existing.decor().clone();
+            *existing = value;
+        }
+        None => document[key] = toml_edit::value(value),
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, document.to_string())
+}
+
+/// Where the value of a config option came from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfigSource {
+    Default,
+    File,
+    Env,
+    Cli,
+}
+
+impl ConfigSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConfigSource::Default => "default",
+            ConfigSource::File => "config.toml",
+            ConfigSource::Env => "env",
+            ConfigSource::Cli => "cli",
+        }
+    }
+
+    /// Finds the source of `key`, given the providers that were merged over the defaults.
+    /// Later sources take precedence, matching the order they're merged in `main`.
+    pub fn of(key: &str, file: &Figment, env: &Figment, cli: &Figment) -> ConfigSource {
+        if cli.contains(key) {
+            ConfigSource::Cli
+        } else if env.contains(key) {
+            ConfigSource::Env
+        } else if file.contains(key) {
+            ConfigSource::File
+        } else {
+            ConfigSource::Default
+        }
+    }
+}
+
+/// The value of a single config option, as shown and edited on the settings screen.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum SettingValue {
+    Bool(bool),
+    Number(i64),
+    Text(String),
+}
+
+impl SettingValue {
+    fn from_json(value: serde_json::Value) -> Option<SettingValue> {
+        match value {
+            serde_json::Value::Bool(value) => Some(SettingValue::Bool(value)),
+            serde_json::Value::Number(value) => value.as_i64().map(SettingValue::Number),
+            serde_json::Value::String(value) => Some(SettingValue::Text(value)),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SettingValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingValue::Bool(value) => write!(f, "{}", value),
+            SettingValue::Number(value) => write!(f, "{}", value),
+            SettingValue::Text(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<SettingValue> for Value {
+    fn from(value: SettingValue) -> Self {
+        match value {
+            SettingValue::Bool(value) => value.into(),
+            SettingValue::Number(value) => value.into(),
+            SettingValue::Text(value) => value.into(),
+        }
+    }
+}
+
+/// The values a setting can be changed to.
+pub enum SettingKind {
+    Bool,
+    Number { min: i64, max: i64, step: i64 },
+    // One of a fixed list of values, cycled through in order.
+    Choice(Vec<String>),
+    // Shown with where it came from, but too complex to change on the settings screen.
+    ReadOnly,
+}
+
+/// A config option that can be changed on the settings screen.
+pub struct Setting {
+    pub key: &'static str,
+    pub kind: SettingKind,
+}
+
+impl Setting {
+    /// The option's current value, as shown on the settings screen.
+    pub fn value_label(&self, config: &Config) -> String {
+        match self.kind {
+            SettingKind::ReadOnly => "edit config.toml".to_string(),
+            _ => config
+                .get(self.key)
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the value `steps` steps away from `value`. Numbers are clamped to their range,
+    /// and choices wrap around.
+    pub fn step(&self, value: &SettingValue, steps: i64) -> SettingValue {
+        match (&self.kind, value) {
+            (SettingKind::Bool, SettingValue::Bool(value)) => {
+                SettingValue::Bool(*value ^ (steps % 2 != 0))
+            }
+            (SettingKind::Number { min, max, step }, SettingValue::Number(value)) => {
+                SettingValue::Number((value + steps * step).clamp(*min, *max))
+            }
+            (SettingKind::Choice(choices), SettingValue::Text(value)) if !choices.is_empty() => {
+                let index = choices.iter().position(|choice| choice == value).unwrap_or(0);
+                let index = (index as i64 + steps).rem_euclid(choices.len() as i64);
+                SettingValue::Text(choices[index as usize].clone())
+            }
+            _ => value.clone(),
+        }
+    }
+}
+
+fn choices<T: ValueEnum>() -> SettingKind {
+    SettingKind::Choice(
+        T::value_variants()
+            .iter()
+            .filter_map(|variant| variant.to_possible_value())
+            .map(|value| value.get_name().to_string())
+            .collect(),
+    )
+}
+
+/// Every config option, in the order they're shown on the settings screen.
+pub fn settings(theme_names: &[String]) -> Vec<Setting> {
+    let themes = || SettingKind::Choice(theme_names.to_vec());
+    let auto_or_themes = [AUTO_THEME.to_string()].into_iter().chain(theme_names.iter().cloned());
+    let number = |min, max, step| SettingKind::Number { min, max, step };
+    [
+        ("mode", choices::<Mode>()),
+        ("time", number(5, 3600, 5)),
+        ("word_list", choices::<WordList>()),
+        ("punctuation", SettingKind::Bool),
+        ("theme", SettingKind::Choice(auto_or_themes.collect())),
+        ("light_theme", themes()),
+        ("dark_theme", themes()),
+        ("target_wpm", number(0, 300, 5)),
+        ("cursor", choices::<CursorType>()),
+        ("current_word", choices::<CurrentWord>()),
+        ("error_indicator", choices::<ErrorIndicator>()),
+        ("sudden_death", SettingKind::Bool),
+        ("min_accuracy", number(0, 100, 5)),
+        ("blind", SettingKind::Bool),
+        ("memory", number(0, 60, 1)),
+        ("colors", choices::<ColorDepth>()),
+        ("keybindings", SettingKind::ReadOnly),
+    ]
+    .into_iter()
+    .map(|(key, kind)| Setting { key, kind })
+    .collect()
+}
+
+impl Config {
+    /// The name of the theme to show. When `theme` is "auto", that's the light or dark theme,
+    /// depending on the terminal's background colour (dark if it couldn't be detected).
+    pub fn resolved_theme(&self, background: Option<Background>) -> &str {
+        match (self.theme.as_str(), background) {
+            (AUTO_THEME, Some(Background::Light)) => &self.light_theme,
+            (AUTO_THEME, Some(Background::Dark) | None) => &self.dark_theme,
+            (theme, _) => theme,
+        }
+    }
+
+    /// The current value of a config option, by its name in `config.toml`.
+    pub fn get(&self, key: &str) -> Option<SettingValue> {
+        let mut values = serde_json::to_value(self).ok()?;
+        SettingValue::from_json(values.get_mut(key)?.take())
+    }
+
+    /// Returns a copy of the config with a single option changed, or `None` if the value
+    /// isn't valid for that option.
+    pub fn with_value(&self, key: &str, value: SettingValue) -> Option<Config> {
+        Figment::from(Serialized::defaults(self))
+            .merge(Serialized::default(key, value))
+            .extract()
+            .ok()
+    }
+}
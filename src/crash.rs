@@ -0,0 +1,56 @@
+//! Crash handling. If o4t panics, the terminal is put back the way we found it and a crash
+//! report is written, rather than leaving the user's shell in raw mode.
+
+use crate::config::Config;
+use crate::terminal::restore_terminal;
+use std::backtrace::Backtrace;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, io};
+
+// The config in use, as JSON, so that it can be included in crash reports. It's kept up to
+// date as the config is changed from the settings screen or command palette.
+static CURRENT_CONFIG: Mutex<String> = Mutex::new(String::new());
+
+pub fn set_current_config(config: &Config) {
+    let json = serde_json::to_string_pretty(config).unwrap_or_default();
+    *CURRENT_CONFIG.lock().unwrap_or_else(PoisonError::into_inner) = json;
+}
+
+/// Installs a panic hook which restores the terminal and writes a crash report to
+/// `reports_dir`, then tells the user where the report went.
+pub fn install_panic_hook(reports_dir: PathBuf) {
+    panic::set_hook(Box::new(move |info| {
+        // If this fails there's nothing more we can do, and the report is more important.
+        let _ = restore_terminal();
+        let report = crash_report(info);
+        eprintln!("o4t crashed: {}", info);
+        match write_crash_report(&reports_dir, &report) {
+            Ok(path) => eprintln!("A crash report was written to {}", path.display()),
+            Err(err) => eprintln!("Couldn't write a crash report ({}):\n\n{}", err, report),
+        }
+    }));
+}
+
+fn crash_report(info: &PanicHookInfo) -> String {
+    let config = CURRENT_CONFIG.lock().unwrap_or_else(PoisonError::into_inner);
+    format!(
+        "o4t {} crashed: {}\n\nBacktrace:\n{}\n\nConfig:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        info,
+        Backtrace::force_capture(),
+        config
+    )
+}
+
+fn write_crash_report(reports_dir: &Path, report: &str) -> io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    fs::create_dir_all(reports_dir)?;
+    let path = reports_dir.join(format!("crash-{}.txt", timestamp));
+    fs::write(&path, report)?;
+    Ok(path)
+}
@@ -0,0 +1,41 @@
+/// Scores how well `query` matches `candidate`, case-insensitively. Every character of the
+/// query must appear in the candidate in order, otherwise there's no match and `None` is
+/// returned. Higher scores are better matches: consecutive characters, and characters at
+/// the start of a word, score more.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate = candidate.to_lowercase().chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+    for query_char in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let offset = candidate[position..]
+            .iter()
+            .position(|&c| c == query_char)?;
+        let index = position + offset;
+        score += 1;
+        if previous_match.is_some_and(|previous| previous + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !candidate[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous_match = Some(index);
+        position = index + 1;
+    }
+    // Prefer shorter candidates when scores are otherwise equal.
+    Some(score * 100 - candidate.len() as i64)
+}
+
+/// Keeps the items whose key matches `query`, best matches first. Items with equal scores
+/// keep their original order, and an empty query keeps every item.
+pub fn fuzzy_filter<T>(query: &str, items: Vec<T>, key: impl Fn(&T) -> &str) -> Vec<T> {
+    if query.trim().is_empty() {
+        return items;
+    }
+    let mut scored = items
+        .into_iter()
+        .filter_map(|item| fuzzy_score(query, key(&item)).map(|score| (score, item)))
+        .collect::<Vec<_>>();
+    scored.sort_by_key(|(score, _)| -score);
+    scored.into_iter().map(|(_, item)| item).collect()
+}
@@ -0,0 +1,486 @@
+use crate::clock::Clock;
+use crate::generator::WordGenerator;
+use crate::words;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::cmp::max;
+use std::ops::Div;
+use std::rc::Rc;
+use std::time::Duration;
+
+// Words are generated lazily, keeping at least this many words ahead of the cursor.
+const NUMBER_OF_WORDS_AHEAD: usize = 100;
+// The number of characters that must be typed before the minimum accuracy is enforced.
+const MIN_ACCURACY_WARM_UP_CHARS: usize = 20;
+
+#[derive(ValueEnum, Clone, Debug, Copy, PartialEq, Serialize, Deserialize)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum Mode {
+    // Type the generated words until the timer runs out.
+    Time,
+    // No words or timer - whatever the user types is accepted until they finish the game.
+    Zen,
+}
+
+#[derive(ValueEnum, Clone, Debug, Copy, PartialEq, Serialize, Deserialize)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum WordList {
+    // The 1000 most common English words.
+    #[value(name = "english-1k")]
+    #[serde(rename = "english-1k")]
+    English1k,
+    // The 200 most common English words.
+    #[value(name = "english-200")]
+    #[serde(rename = "english-200")]
+    English200,
+}
+
+impl WordList {
+    pub fn words(&self) -> &'static [&'static str] {
+        match self {
+            WordList::English1k => &words::ENGLISH_1K_WORDS,
+            // The word list is sorted by how common each word is.
+            WordList::English200 => &words::ENGLISH_1K_WORDS[..200],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailReason {
+    // A character was mistyped while sudden death was enabled.
+    SuddenDeath,
+    // Accuracy dropped below the minimum accuracy after the warm-up period.
+    MinAccuracy,
+}
+
+impl FailReason {
+    pub fn description(&self) -> &'static str {
+        match self {
+            FailReason::SuddenDeath => "sudden death",
+            FailReason::MinAccuracy => "accuracy too low",
+        }
+    }
+}
+
+#[derive(Debug, PartialOrd, PartialEq)]
+pub struct WordAttempt {
+    // the word the user was asked and attempted to type
+    pub word: String,
+    // what the user typed for this word
+    pub user_attempt: String,
+}
+
+impl WordAttempt {
+    pub fn new(word: String) -> WordAttempt {
+        WordAttempt {
+            word,
+            user_attempt: String::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Score {
+    // Number of characters matching what they should be at the current point in time.
+    pub character_matches: usize,
+    // Number of characters which don't match what they should be at the current point in time.
+    // This value can decrease if the user corrects a typo.
+    pub character_mismatches: usize,
+    // The number of correctly typed characters in this session. Always increasing.
+    pub character_hits: usize,
+    // The number of characters which were typed which shouldn't have been in this session.
+    // This number cannot decrease. If you make a typo, it remains in this value.
+    pub character_misses: usize,
+    // The ratio of character_hits / character_hits + character_misses
+    pub accuracy: f32,
+    // Number of characters typed per minute.
+    pub chars_per_minute: f32,
+    // WPM = (character_matches * 5) * (60 / session_length_secs)
+    pub wpm: f32,
+    // Number of words typed CORRECTLY per minute.
+    pub real_words_per_minute: f32,
+    // Total number of CORRECTLY typed words.
+    pub num_words: usize,
+    // The number of words typed correctly in a row. Always increasing. Words that were typed
+    // incorrectly then changed don't count.
+    pub best_char_streak: usize,
+    pub current_char_streak: usize,
+}
+
+impl Score {
+    pub fn is_perfect(&self) -> bool {
+        self.character_misses == 0 && self.num_words > 0
+    }
+}
+
+/// The rules of a single game.
+#[derive(Clone, Debug)]
+pub struct GameConfig {
+    pub mode: Mode,
+    // The length of the game. Ignored in zen mode, where the user decides when to finish.
+    pub time: Duration,
+    // Fail the game as soon as a character is mistyped.
+    pub sudden_death: bool,
+    // If non-zero, fail the game when accuracy drops below this percentage.
+    pub min_accuracy: usize,
+}
+
+/// Something the user did, translated from whatever input device they're using.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputEvent {
+    // A typed character. Typing a space moves on to the next word.
+    Char(char),
+    Backspace,
+    // Deletes the word being typed, or goes back to the previous word if nothing's been typed.
+    DeleteWord,
+    // Finishes the game in zen mode.
+    Finish,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameState {
+    // Waiting for the user to start typing.
+    Waiting,
+    Active,
+    // The game is over, either because the time ran out or the user finished it, or because
+    // a fail condition was met.
+    Finished(Option<FailReason>),
+}
+
+/// A single typing test, independent of how it's displayed.
+///
+/// All timing comes from the game's clock, so a game can be driven by real time or, in tests,
+/// by a [`crate::clock::ManualClock`].
+pub struct Game {
+    config: GameConfig,
+    // Contains the history of words for the current game, and some words ahead of the cursor.
+    words: Vec<WordAttempt>,
+    // Where new words are pulled from as the user types through the existing ones.
+    word_generator: Box<dyn WordGenerator>,
+    // The current input the user has typed while trying to type words[current_word_offset].
+    current_user_input: String,
+    // The index of the word in words that is being attempted by the user.
+    current_word_offset: usize,
+    score: Score,
+    state: GameState,
+    clock: Rc<dyn Clock>,
+    // When the user started typing, and when the game finished, according to the clock.
+    started_at: Duration,
+    finished_at: Duration,
+}
+
+impl Game {
+    pub fn new(
+        config: GameConfig,
+        word_generator: Box<dyn WordGenerator>,
+        clock: Rc<dyn Clock>,
+    ) -> Game {
+        let words = match config.mode {
+            Mode::Time => vec![],
+            // In zen mode the words are built up from what the user types.
+            Mode::Zen => vec![WordAttempt::new(String::new())],
+        };
+        let mut game = Game {
+            config,
+            words,
+            word_generator,
+            current_user_input: String::new(),
+            current_word_offset: 0,
+            score: Score::default(),
+            state: GameState::Waiting,
+            clock,
+            started_at: Duration::ZERO,
+            finished_at: Duration::ZERO,
+        };
+        game.generate_words();
+        game
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn words(&self) -> &[WordAttempt] {
+        &self.words
+    }
+
+    pub fn current_user_input(&self) -> &str {
+        &self.current_user_input
+    }
+
+    pub fn current_word_offset(&self) -> usize {
+        self.current_word_offset
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.state == GameState::Active
+    }
+
+    pub fn is_zen_mode(&self) -> bool {
+        self.config.mode == Mode::Zen
+    }
+
+    /// When the user started typing, if they have.
+    pub fn started_at(&self) -> Option<Duration> {
+        match self.state {
+            GameState::Waiting => None,
+            GameState::Active | GameState::Finished(_) => Some(self.started_at),
+        }
+    }
+
+    /// How long the game has been running for, or how long it ran for if it's finished.
+    pub fn elapsed(&self) -> Duration {
+        let elapsed = match self.state {
+            GameState::Waiting => Duration::ZERO,
+            GameState::Active => self.clock.now().saturating_sub(self.started_at),
+            GameState::Finished(_) => self.finished_at.saturating_sub(self.started_at),
+        };
+        if self.is_zen_mode() {
+            elapsed
+        } else {
+            elapsed.min(self.config.time)
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.config.time.saturating_sub(self.elapsed())
+    }
+
+    /// Refreshes the score, and ends the game if the time is up. Should be called regularly
+    /// while the game is running.
+    pub fn tick(&mut self) {
+        if !self.is_active() {
+            return;
+        }
+        let end = self.started_at + self.config.time;
+        if !self.is_zen_mode() && self.clock.now() >= end {
+            // The game ended when the time ran out, rather than whenever we noticed.
+            self.finish_at(end, None);
+        } else {
+            self.refresh_score();
+        }
+    }
+
+    pub fn handle_input(&mut self, event: InputEvent) {
+        self.tick();
+        if let GameState::Finished(_) = self.state {
+            return;
+        }
+        match event {
+            InputEvent::Char(' ') => {
+                // Spaces only move on to the next word once something has been typed.
+                if !self.current_user_input.is_empty() {
+                    self.next_word();
+                }
+            }
+            InputEvent::Char(char) => self.type_char(char),
+            InputEvent::Backspace if self.is_active() => {
+                if self.current_user_input.pop().is_none() {
+                    self.go_back_to_previous_word();
+                }
+                self.sync_zen_word();
+            }
+            InputEvent::Backspace => {}
+            InputEvent::DeleteWord => {
+                if self.current_user_input.is_empty() {
+                    self.go_back_to_previous_word();
+                } else {
+                    self.current_user_input = String::new();
+                }
+                self.sync_zen_word();
+            }
+            // Zen mode has no timer, so the user decides when the game is over.
+            InputEvent::Finish if self.is_zen_mode() && self.is_active() => self.finish(None),
+            InputEvent::Finish => {}
+        }
+    }
+
+    fn type_char(&mut self, char: char) {
+        // Anything typed in zen mode is correct.
+        if self.is_zen_mode() {
+            self.words[self.current_word_offset].word.push(char);
+        }
+        let current_word = &self.words[self.current_word_offset].word;
+        let cursor_offset = self.current_user_input.chars().count();
+        let expected_char = current_word.chars().nth(cursor_offset);
+        if let Some(expected_char) = expected_char {
+            if char == expected_char {
+                self.score.current_char_streak += 1;
+                self.score.character_hits += 1;
+            } else {
+                self.score.current_char_streak = 0;
+                self.score.character_misses += 1;
+            }
+        } else {
+            // User has gone beyond the word and is typing extra characters.
+            self.score.character_misses += 1;
+            self.score.current_char_streak = 0;
+        }
+        self.score.best_char_streak = max(
+            self.score.best_char_streak,
+            self.score.current_char_streak,
+        );
+
+        // Pressing any character, while the game hasn't started, starts the game
+        if self.state == GameState::Waiting {
+            self.state = GameState::Active;
+            self.started_at = self.clock.now();
+        }
+        self.current_user_input.push(char);
+
+        if let Some(fail_reason) = self.check_fail_conditions() {
+            self.finish(Some(fail_reason));
+        }
+    }
+
+    fn next_word(&mut self) {
+        self.words[self.current_word_offset].user_attempt = self.current_user_input.clone();
+        self.current_word_offset += 1;
+        self.current_user_input = String::new();
+        if self.is_zen_mode() {
+            self.words.push(WordAttempt::new(String::new()));
+        }
+        self.generate_words();
+        // A finite word generator has run out, and the user has typed every word.
+        if self.current_word_offset >= self.words.len() {
+            self.finish(None);
+        }
+    }
+
+    // Go back into the previous word if possible. Words that were typed correctly can't be
+    // changed.
+    fn go_back_to_previous_word(&mut self) {
+        if self.is_active()
+            && self.current_word_offset != 0
+            && self.words[self.current_word_offset - 1].user_attempt
+                != self.words[self.current_word_offset - 1].word
+        {
+            self.current_word_offset -= 1;
+            self.current_user_input = self.words[self.current_word_offset].user_attempt.clone();
+        }
+    }
+
+    /// Pulls words from the word generator until there are enough words ahead of the cursor.
+    fn generate_words(&mut self) {
+        while self.words.len() < self.current_word_offset + NUMBER_OF_WORDS_AHEAD {
+            match self.word_generator.next_word() {
+                Some(word) => self.words.push(WordAttempt::new(word)),
+                None => break,
+            }
+        }
+    }
+
+    /// In zen mode, the current word is whatever the user has typed so far.
+    fn sync_zen_word(&mut self) {
+        if self.is_zen_mode() {
+            self.words[self.current_word_offset].word = self.current_user_input.clone();
+        }
+    }
+
+    fn finish(&mut self, fail_reason: Option<FailReason>) {
+        self.finish_at(self.clock.now(), fail_reason);
+    }
+
+    fn finish_at(&mut self, time: Duration, fail_reason: Option<FailReason>) {
+        let was_active = self.is_active();
+        self.finished_at = time;
+        self.state = GameState::Finished(fail_reason);
+        if was_active {
+            // The elapsed time is now fixed at `time`, so this is the final score.
+            self.refresh_score();
+        }
+    }
+
+    /// Returns the reason the current game should fail, if any of the configured
+    /// fail conditions have been met.
+    fn check_fail_conditions(&self) -> Option<FailReason> {
+        let hits = self.score.character_hits;
+        let misses = self.score.character_misses;
+        if self.config.sudden_death && misses > 0 {
+            return Some(FailReason::SuddenDeath);
+        }
+
+        let typed = hits + misses;
+        if self.config.min_accuracy > 0 && typed >= MIN_ACCURACY_WARM_UP_CHARS {
+            let accuracy_percent = hits as f32 / typed as f32 * 100.;
+            if accuracy_percent < self.config.min_accuracy as f32 {
+                return Some(FailReason::MinAccuracy);
+            }
+        }
+        None
+    }
+
+    fn refresh_score(&mut self) {
+        let mut character_matches: usize = 0;
+        let mut character_mismatches: usize = 0;
+        let mut num_correct_words: usize = 0;
+
+        // Count hits and misses
+        for (index, attempt) in self.words.iter().enumerate() {
+            let zipped_chars = if index != self.current_word_offset {
+                attempt.user_attempt.chars().zip(attempt.word.chars())
+            } else {
+                self.current_user_input.chars().zip(attempt.word.chars())
+            };
+            let mut this_word_hits = 0;
+            for (user_char, expected_char) in zipped_chars {
+                let is_hit = user_char == expected_char;
+                if is_hit {
+                    character_matches += 1;
+                    this_word_hits += 1;
+                } else {
+                    character_mismatches += 1;
+                }
+            }
+            if !attempt.word.is_empty() && this_word_hits == attempt.word.chars().count() {
+                num_correct_words += 1
+            }
+        }
+
+        let character_hits = self.score.character_hits;
+        let character_misses = self.score.character_misses;
+        let accuracy =
+            (character_hits as f32).div(character_hits.saturating_add(character_misses) as f32);
+
+        let num_chars = character_matches.saturating_add(character_mismatches);
+
+        // Chars and words per minute
+        let seconds_elapsed = self.elapsed().as_secs_f32();
+        let minutes_elapsed = seconds_elapsed / 60.;
+        let chars_per_minute = num_chars as f32 / minutes_elapsed;
+        let real_words_per_minute = num_correct_words as f32 / minutes_elapsed;
+        // We add the num_correct_words below as it represents the number of spaces, which should
+        // be included in the WPM calculation.
+        let mut wpm =
+            ((character_matches + num_correct_words) as f32 / 5.) * (60. / seconds_elapsed);
+
+        if wpm.is_infinite() {
+            wpm = 0.;
+        }
+
+        self.score = Score {
+            character_matches,
+            character_mismatches,
+            character_hits: self.score.character_hits,
+            character_misses: self.score.character_misses,
+            accuracy,
+            chars_per_minute,
+            wpm,
+            real_words_per_minute,
+            num_words: num_correct_words,
+            best_char_streak: self.score.best_char_streak,
+            current_char_streak: self.score.current_char_streak,
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use rand::rngs::ThreadRng;
+use rand::seq::IndexedRandom;
+use rand::Rng;
+
+/// A source of words for the user to type.
+///
+/// Words are pulled lazily as the user approaches the end of the words generated so far,
+/// so a generator may be endless (random words) or finite (a quote or a piece of text).
+pub trait WordGenerator {
+    /// Returns the next word, or `None` if the generator has run out of words.
+    fn next_word(&mut self) -> Option<String>;
+}
+
+/// Endlessly picks random words from a word list, never picking the same word twice in a row.
+pub struct RandomWords {
+    words: &'static [&'static str],
+    previous: Option<&'static str>,
+    rng: ThreadRng,
+}
+
+impl RandomWords {
+    pub fn new(words: &'static [&'static str]) -> RandomWords {
+        RandomWords {
+            words,
+            previous: None,
+            rng: rand::rng(),
+        }
+    }
+}
+
+impl WordGenerator for RandomWords {
+    fn next_word(&mut self) -> Option<String> {
+        let mut word = *self.words.choose(&mut self.rng)?;
+        if self.words.len() > 1 {
+            while Some(word) == self.previous {
+                word = *self.words.choose(&mut self.rng)?;
+            }
+        }
+        self.previous = Some(word);
+        Some(word.to_string())
+    }
+}
+
+/// Generates no words at all, for modes where the user decides what to type (e.g. zen mode).
+pub struct NoWords;
+
+impl WordGenerator for NoWords {
+    fn next_word(&mut self) -> Option<String> {
+        None
+    }
+}
+
+/// The words of a piece of text, in order, for typing out arbitrary text (e.g. a file).
+/// Runs out once every word has been generated.
+pub struct TextWords {
+    words: std::vec::IntoIter<String>,
+}
+
+impl TextWords {
+    /// Splits the text into words on whitespace. Use [`normalize_text`] first to get rid of
+    /// characters that can't easily be typed.
+    pub fn new(text: &str) -> TextWords {
+        let words = text.split_whitespace().map(str::to_string).collect::<Vec<_>>();
+        TextWords {
+            words: words.into_iter(),
+        }
+    }
+}
+
+impl WordGenerator for TextWords {
+    fn next_word(&mut self) -> Option<String> {
+        self.words.next()
+    }
+}
+
+/// Tidies up text so it can be typed: invisible characters are removed and every run of
+/// whitespace (including line breaks and non-breaking spaces) becomes a single space. With
+/// `ascii`, typographic punctuation like smart quotes, dashes and ellipses is replaced with
+/// the nearest characters on a keyboard.
+pub fn normalize_text(text: &str, ascii: bool) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            // Zero width spaces and joiners, soft hyphens and byte order marks.
+            '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{00ad}' | '\u{feff}' => {}
+            char if char.is_whitespace() => normalized.push(' '),
+            // Control characters other than whitespace can't be typed at all.
+            char if char.is_control() => {}
+            '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}' | '\u{2032}' if ascii => {
+                normalized.push('\'')
+            }
+            '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{201f}' | '\u{2033}' if ascii => {
+                normalized.push('"')
+            }
+            '\u{2010}'..='\u{2015}' | '\u{2212}' if ascii => normalized.push('-'),
+            '\u{2026}' if ascii => normalized.push_str("..."),
+            char => normalized.push(char),
+        }
+    }
+    normalized.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Wraps another generator, capitalising the start of each sentence and adding punctuation
+/// between words.
+pub struct Punctuated {
+    inner: Box<dyn WordGenerator>,
+    start_of_sentence: bool,
+    rng: ThreadRng,
+}
+
+impl Punctuated {
+    pub fn new(inner: Box<dyn WordGenerator>) -> Punctuated {
+        Punctuated {
+            inner,
+            start_of_sentence: true,
+            rng: rand::rng(),
+        }
+    }
+}
+
+impl WordGenerator for Punctuated {
+    fn next_word(&mut self) -> Option<String> {
+        let word = self.inner.next_word()?;
+        let mut chars = word.chars();
+        let mut word = match chars.next() {
+            Some(first) if self.start_of_sentence => first.to_uppercase().chain(chars).collect(),
+            _ => word,
+        };
+        // Roughly one word in ten ends a sentence, and a few more are followed by a comma.
+        let roll = self.rng.random_range(0..100);
+        let punctuation = match roll {
+            0..=7 => Some('.'),
+            8 => Some('?'),
+            9 => Some('!'),
+            10..=17 => Some(','),
+            18 => Some(';'),
+            _ => None,
+        };
+        self.start_of_sentence = matches!(punctuation, Some('.' | '?' | '!'));
+        word.extend(punctuation);
+        Some(word)
+    }
+}
@@ -0,0 +1,171 @@
+use crate::app::App;
+use o4t::game::{FailReason, GameState, Mode, Score};
+use serde::{Deserialize, Serialize};
+use std::fs::{create_dir_all, File, OpenOptions};
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Outcome {
+    Completed,
+    Failed,
+}
+
+/// A single finished game, as stored in the history file.
+#[derive(Serialize, Debug)]
+pub struct GameRecord {
+    // Seconds since the unix epoch at the time the game finished.
+    pub timestamp: u64,
+    pub mode: Mode,
+    // The length of the game in seconds. That's the time limit, except for zen games, which
+    // don't have one and record how long they actually lasted.
+    pub time: usize,
+    pub outcome: Outcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fail_reason: Option<FailReason>,
+    #[serde(flatten)]
+    pub score: Score,
+}
+
+impl GameRecord {
+    pub fn from_app(app: &App) -> GameRecord {
+        let fail_reason = match app.game.state() {
+            GameState::Finished(reason) => reason,
+            _ => None,
+        };
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        GameRecord {
+            timestamp,
+            mode: app.game.config().mode,
+            time: if app.game.is_zen_mode() {
+                app.game.elapsed().as_secs_f32().round() as usize
+            } else {
+                app.game.config().time.as_secs() as usize
+            },
+            outcome: if fail_reason.is_some() {
+                Outcome::Failed
+            } else {
+                Outcome::Completed
+            },
+            fail_reason,
+            score: app.game.score().clone(),
+        }
+    }
+
+    /// Checks the result of a game played with `o4t run` against its targets, returning why
+    /// the game didn't pass if it didn't.
+    pub fn check_targets(
+        &self,
+        min_wpm: Option<f32>,
+        target_accuracy: Option<u8>,
+    ) -> Result<(), String> {
+        let score = &self.score;
+        if let Some(reason) = self.fail_reason {
+            return Err(format!("the game failed: {}", reason.description()));
+        }
+        if let Some(min_wpm) = min_wpm
+            && (score.wpm.is_nan() || score.wpm < min_wpm)
+        {
+            return Err(format!("{:.0} wpm is below the target of {} wpm", score.wpm, min_wpm));
+        }
+        let accuracy = score.accuracy * 100.;
+        if let Some(target_accuracy) = target_accuracy
+            && (accuracy.is_nan() || accuracy < f32::from(target_accuracy))
+        {
+            return Err(format!(
+                "{:.0}% accuracy is below the target of {}%",
+                accuracy, target_accuracy
+            ));
+        }
+        Ok(())
+    }
+}
+
+// The parts of a stored game that are needed to summarise the history.
+#[derive(Deserialize)]
+struct StoredGame {
+    outcome: Outcome,
+    // NaN scores (e.g. from a game with nothing typed) are stored as null.
+    wpm: Option<f32>,
+    accuracy: Option<f32>,
+}
+
+// The number of most recent games included in Stats::recent_wpm.
+const RECENT_GAMES: usize = 10;
+
+/// A summary of every game in the history file.
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub games: usize,
+    pub completed: usize,
+    // The following only include completed games.
+    pub best_wpm: f32,
+    pub average_wpm: f32,
+    pub average_accuracy: f32,
+    // The average wpm of the most recent completed games.
+    pub recent_wpm: f32,
+}
+
+/// Appends finished games to a JSON lines file in the user's data directory.
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(path: PathBuf) -> History {
+        History { path }
+    }
+
+    pub fn record(&self, record: &GameRecord) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let line = serde_json::to_string(record)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// Summarises the games recorded so far. Lines that can't be read (e.g. from an older
+    /// version of o4t) are skipped.
+    pub fn stats(&self) -> io::Result<Stats> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Stats::default()),
+            Err(err) => return Err(err),
+        };
+        let mut games = 0;
+        let mut completed = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let Ok(game) = serde_json::from_str::<StoredGame>(&line?) else {
+                continue;
+            };
+            games += 1;
+            if game.outcome == Outcome::Completed {
+                completed.push(game);
+            }
+        }
+
+        let average = |games: &[StoredGame], value: fn(&StoredGame) -> Option<f32>| {
+            let values = games.iter().filter_map(value);
+            let (sum, count) = values.fold((0., 0), |(sum, count), value| (sum + value, count + 1));
+            if count == 0 { 0. } else { sum / count as f32 }
+        };
+        let recent = &completed[completed.len().saturating_sub(RECENT_GAMES)..];
+        Ok(Stats {
+            games,
+            completed: completed.len(),
+            best_wpm: completed.iter().filter_map(|game| game.wpm).fold(0., f32::max),
+            average_wpm: average(&completed, |game| game.wpm),
+            average_accuracy: average(&completed, |game| game.accuracy),
+            recent_wpm: average(recent, |game| game.wpm),
+        })
+    }
+}
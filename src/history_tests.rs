@@ -0,0 +1,51 @@
+//! Tests for the records of finished games kept in the history.
+
+use crate::app::{get_themes, App};
+use crate::config::Config;
+use crate::history::GameRecord;
+use o4t::clock::ManualClock;
+use o4t::game::{InputEvent, Mode};
+use std::rc::Rc;
+use std::time::Duration;
+
+fn app(config: Config, clock: &ManualClock) -> App {
+    App::with_config(Rc::new(config), get_themes(), Rc::new(clock.clone()))
+}
+
+fn type_text(app: &mut App, text: &str) {
+    for char in text.chars() {
+        app.game.handle_input(InputEvent::Char(char));
+    }
+}
+
+#[test]
+fn text_games_are_recorded_as_timed_games() {
+    // Typing text is always timed, even when the config asks for zen mode.
+    let config = Config {
+        mode: Mode::Zen,
+        time: 15,
+        ..Config::default()
+    };
+    let clock = ManualClock::new();
+    let mut app = app(config, &clock).with_text(Rc::from("to be"));
+    type_text(&mut app, "to be ");
+    let record = GameRecord::from_app(&app);
+    assert_eq!(record.mode, Mode::Time);
+    assert_eq!(record.time, 15);
+}
+
+#[test]
+fn zen_games_record_how_long_they_lasted() {
+    let config = Config {
+        mode: Mode::Zen,
+        ..Config::default()
+    };
+    let clock = ManualClock::new();
+    let mut app = app(config, &clock);
+    type_text(&mut app, "hello there");
+    clock.advance(Duration::from_secs(12));
+    app.game.handle_input(InputEvent::Finish);
+    let record = GameRecord::from_app(&app);
+    assert_eq!(record.mode, Mode::Zen);
+    assert_eq!(record.time, 12);
+}
@@ -0,0 +1,315 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Something the user can do with a key binding.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Quit,
+    Restart,
+    ThemePicker,
+    CommandPalette,
+    Settings,
+    // Finishes the game in zen mode.
+    Finish,
+    // Deletes the word being typed, or goes back to the previous word.
+    DeleteWord,
+}
+
+impl Action {
+    /// The action's name in the `[keybindings]` table of config.toml.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Restart => "restart",
+            Action::ThemePicker => "theme_picker",
+            Action::CommandPalette => "command_palette",
+            Action::Settings => "settings",
+            Action::Finish => "finish",
+            Action::DeleteWord => "delete_word",
+        }
+    }
+}
+
+/// A key along with the modifiers held down, written like "ctrl+t" or "esc" in config.toml.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyChord {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        let mut code = code;
+        match code {
+            // Shift is part of the character itself (e.g. "T" rather than "shift+t"), and
+            // terminals report ctrl+t and ctrl+T the same way.
+            KeyCode::Char(char) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                if !modifiers.is_empty() {
+                    code = KeyCode::Char(char.to_ascii_lowercase());
+                }
+            }
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                code = KeyCode::BackTab;
+                modifiers.remove(KeyModifiers::SHIFT);
+            }
+            KeyCode::BackTab => modifiers.remove(KeyModifiers::SHIFT),
+            _ => {}
+        }
+        KeyChord { code, modifiers }
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        *self == KeyChord::new(key.code, key.modifiers)
+    }
+
+    /// Whether pressing this chord would normally type or delete a character, so binding it
+    /// would make it impossible to type some words.
+    pub fn is_typeable(&self) -> bool {
+        let unmodified = !self
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        unmodified && matches!(self.code, KeyCode::Char(_) | KeyCode::Backspace)
+    }
+
+    /// A short label for the chord, like "^T" or "ESC", as shown in the footer.
+    pub fn label(&self) -> String {
+        if let KeyCode::Char(char) = self.code
+            && self.modifiers == KeyModifiers::CONTROL
+        {
+            return format!("^{}", char.to_ascii_uppercase());
+        }
+        self.to_string().to_uppercase()
+    }
+}
+
+// The names of keys other than characters and function keys.
+const KEY_NAMES: [(&str, KeyCode); 15] = [
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("enter", KeyCode::Enter),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid key binding \"{}\"", value);
+        // Split on the last "+", so that "ctrl++" binds the plus key.
+        let (modifier_names, key) = match value.rsplit_once('+') {
+            Some((modifiers, "")) => (modifiers.strip_suffix('+').unwrap_or(modifiers), "+"),
+            Some((modifiers, key)) => (modifiers, key),
+            None => ("", value),
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for name in modifier_names.split('+').filter(|name| !name.is_empty()) {
+            modifiers |= match name.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("{}: unknown modifier \"{}\"", invalid(), name)),
+            };
+        }
+
+        let lowercase_key = key.to_lowercase();
+        let mut chars = key.chars();
+        let named_key = KEY_NAMES.iter().find(|(name, _)| *name == lowercase_key);
+        let code = if let Some(&(_, code)) = named_key {
+            code
+        } else if lowercase_key == "space" {
+            KeyCode::Char(' ')
+        } else if let Some(number) = lowercase_key.strip_prefix('f')
+            && let Ok(number) = number.parse::<u8>()
+            && (1..=12).contains(&number)
+        {
+            KeyCode::F(number)
+        } else if let (Some(char), None) = (chars.next(), chars.next()) {
+            KeyCode::Char(char)
+        } else {
+            return Err(format!("{}: unknown key \"{}\"", invalid(), key));
+        };
+        Ok(KeyChord::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}", name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(char) => write!(f, "{}", char),
+            KeyCode::F(number) => write!(f, "f{}", number),
+            code => {
+                let name = KEY_NAMES
+                    .iter()
+                    .find(|(_, key_code)| *key_code == code)
+                    .map_or("?", |(name, _)| name);
+                write!(f, "{}", name)
+            }
+        }
+    }
+}
+
+/// The chords bound to an action. In config.toml this is either a single chord, or a list
+/// of chords.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "ChordsDef", into = "Vec<String>")]
+pub struct Chords(pub Vec<KeyChord>);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChordsDef {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl TryFrom<ChordsDef> for Chords {
+    type Error = String;
+
+    fn try_from(value: ChordsDef) -> Result<Self, Self::Error> {
+        let values = match value {
+            ChordsDef::One(value) => vec![value],
+            ChordsDef::Many(values) => values,
+        };
+        values
+            .iter()
+            .map(|value| value.parse())
+            .collect::<Result<_, _>>()
+            .map(Chords)
+    }
+}
+
+impl From<Chords> for Vec<String> {
+    fn from(chords: Chords) -> Self {
+        chords.0.iter().map(|chord| chord.to_string()).collect()
+    }
+}
+
+impl Chords {
+    fn parse(values: &[&str]) -> Chords {
+        Chords(
+            values
+                .iter()
+                .filter_map(|value| value.parse().ok())
+                .collect(),
+        )
+    }
+}
+
+/// The `[keybindings]` table in config.toml. Actions which aren't in the table keep their
+/// default bindings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyBindings {
+    pub quit: Chords,
+    pub restart: Chords,
+    pub theme_picker: Chords,
+    pub command_palette: Chords,
+    pub settings: Chords,
+    pub finish: Chords,
+    pub delete_word: Chords,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            quit: Chords::parse(&["esc"]),
+            restart: Chords::parse(&["tab"]),
+            theme_picker: Chords::parse(&["ctrl+t"]),
+            command_palette: Chords::parse(&["ctrl+p"]),
+            settings: Chords::parse(&["ctrl+s"]),
+            finish: Chords::parse(&["ctrl+d"]),
+            delete_word: Chords::parse(&["ctrl+w", "ctrl+backspace", "alt+backspace"]),
+        }
+    }
+}
+
+impl KeyBindings {
+    fn all(&self) -> [(Action, &Chords); 7] {
+        [
+            (Action::Quit, &self.quit),
+            (Action::Restart, &self.restart),
+            (Action::ThemePicker, &self.theme_picker),
+            (Action::CommandPalette, &self.command_palette),
+            (Action::Settings, &self.settings),
+            (Action::Finish, &self.finish),
+            (Action::DeleteWord, &self.delete_word),
+        ]
+    }
+
+    /// The action bound to the pressed key, if any.
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        self.all()
+            .into_iter()
+            .find(|(_, chords)| chords.0.iter().any(|chord| chord.matches(key)))
+            .map(|(action, _)| action)
+    }
+
+    /// The label of the first chord bound to an action, for showing in the footer.
+    pub fn label(&self, action: Action) -> String {
+        self.all()
+            .into_iter()
+            .find(|(bound_action, _)| *bound_action == action)
+            .and_then(|(_, chords)| chords.0.first())
+            .map_or_else(String::new, KeyChord::label)
+    }
+
+    /// Checks that every action has a chord, that no chord is bound to more than one action,
+    /// and that no chord would stop the user from typing.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some((action, _)) = self.all().into_iter().find(|(_, chords)| chords.0.is_empty()) {
+            return Err(format!("{} needs at least one key", action.name()));
+        }
+        let bindings = self
+            .all()
+            .into_iter()
+            .flat_map(|(action, chords)| chords.0.iter().map(move |chord| (action, chord)))
+            .collect::<Vec<_>>();
+        for (index, (action, chord)) in bindings.iter().enumerate() {
+            if chord.is_typeable() {
+                return Err(format!(
+                    "\"{}\" can't be bound to {}, since it's needed for typing",
+                    chord,
+                    action.name()
+                ));
+            }
+            if let Some((other_action, _)) = bindings[index + 1..]
+                .iter()
+                .find(|(other_action, other_chord)| other_chord == chord && other_action != action)
+            {
+                return Err(format!(
+                    "\"{}\" is bound to both {} and {}",
+                    chord,
+                    action.name(),
+                    other_action.name()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+//! Tests for reading key bindings from config.toml and checking they can all be used.
+
+use crate::keybindings::{Chords, KeyBindings, KeyChord};
+use ratatui::crossterm::event::{KeyCode, KeyModifiers};
+
+fn chord(value: &str) -> KeyChord {
+    value.parse().unwrap()
+}
+
+#[test]
+fn chords_are_parsed_with_their_modifiers() {
+    assert_eq!(chord("ctrl+t"), KeyChord::new(KeyCode::Char('t'), KeyModifiers::CONTROL));
+    assert_eq!(chord("Control+T"), chord("ctrl+t"));
+    let modifiers = KeyModifiers::CONTROL | KeyModifiers::ALT;
+    assert_eq!(chord("alt+ctrl+x"), KeyChord::new(KeyCode::Char('x'), modifiers));
+    assert_eq!(chord("meta+backspace"), KeyChord::new(KeyCode::Backspace, KeyModifiers::ALT));
+    // Shift is part of the character, and shift+tab is backtab.
+    assert_eq!(chord("shift+a"), KeyChord::new(KeyCode::Char('a'), KeyModifiers::NONE));
+    assert_eq!(chord("shift+tab"), KeyChord::new(KeyCode::BackTab, KeyModifiers::NONE));
+    assert_eq!(chord("ctrl++"), KeyChord::new(KeyCode::Char('+'), KeyModifiers::CONTROL));
+}
+
+#[test]
+fn named_keys_are_parsed() {
+    assert_eq!(chord("esc").code, KeyCode::Esc);
+    assert_eq!(chord("Enter").code, KeyCode::Enter);
+    assert_eq!(chord("pagedown").code, KeyCode::PageDown);
+    assert_eq!(chord("space").code, KeyCode::Char(' '));
+    assert_eq!(chord("f1").code, KeyCode::F(1));
+    assert_eq!(chord("F12").code, KeyCode::F(12));
+}
+
+#[test]
+fn invalid_chords_are_rejected() {
+    let error = |value: &str| value.parse::<KeyChord>().unwrap_err();
+    assert_eq!(
+        error("hyper+t"),
+        "invalid key binding \"hyper+t\": unknown modifier \"hyper\""
+    );
+    assert_eq!(error("ctrl+escape"), "invalid key binding \"ctrl+escape\": unknown key \"escape\"");
+    assert!(error("f13").contains("unknown key"));
+    assert!(error("").contains("unknown key"));
+}
+
+#[test]
+fn chords_are_written_the_way_they_are_read() {
+    for value in ["ctrl+t", "alt+backspace", "esc", "space", "f5", "ctrl+alt+x", "ctrl++", "tab"] {
+        assert_eq!(chord(value).to_string(), value);
+        assert_eq!(chord(&chord(value).to_string()), chord(value));
+    }
+    assert_eq!(chord("Shift+Tab").to_string(), "backtab");
+}
+
+#[test]
+fn chords_have_short_labels() {
+    assert_eq!(chord("ctrl+t").label(), "^T");
+    assert_eq!(chord("esc").label(), "ESC");
+    assert_eq!(chord("alt+backspace").label(), "ALT+BACKSPACE");
+    assert_eq!(KeyBindings::default().label(crate::keybindings::Action::Quit), "ESC");
+}
+
+#[test]
+fn the_default_bindings_are_valid() {
+    assert_eq!(KeyBindings::default().validate(), Ok(()));
+}
+
+#[test]
+fn unusable_bindings_are_rejected() {
+    let bindings = KeyBindings {
+        quit: Chords(vec![]),
+        ..KeyBindings::default()
+    };
+    assert_eq!(bindings.validate(), Err("quit needs at least one key".to_string()));
+
+    let bindings = KeyBindings {
+        restart: Chords(vec![chord("q")]),
+        ..KeyBindings::default()
+    };
+    assert_eq!(
+        bindings.validate(),
+        Err("\"q\" can't be bound to restart, since it's needed for typing".to_string())
+    );
+
+    let bindings = KeyBindings {
+        settings: Chords(vec![chord("ctrl+t")]),
+        ..KeyBindings::default()
+    };
+    assert_eq!(
+        bindings.validate(),
+        Err("\"ctrl+t\" is bound to both theme_picker and settings".to_string())
+    );
+}
@@ -0,0 +1,14 @@
+//! The o4t typing game engine.
+//!
+//! [`game::Game`] holds the state of a single typing test: the words to type, what the user
+//! has typed, and their [`game::Score`]. It's driven by [`game::InputEvent`]s and a
+//! [`clock::Clock`], and has no dependency on the terminal, so it can be embedded in other
+//! tools.
+
+pub mod clock;
+pub mod game;
+pub mod generator;
+pub mod words;
+// Text wrapping for the terminal UI, which needs ratatui.
+#[cfg(feature = "tui")]
+pub mod wrap;
@@ -1,45 +1,92 @@
-use crate::app::{load_score_screen_effect, load_words_effect, App, Screen};
-use crate::cli::Cli;
-use crate::config::Config;
+use crate::book::Book;
+use crate::app::{get_themes, load_words_effect, App, PaletteAction, Screen};
+use crate::cli::{Cli, Command, ThemeCommand};
+use crate::config::{save_config_value, settings, Config, ConfigSource, SettingValue, AUTO_THEME};
+use crate::history::{GameRecord, History};
+use crate::keybindings::Action;
+use crate::palette::{import_theme, slugify, PaletteFormat};
+use crate::theme::{
+    find_theme, load_theme_files, merge_themes, theme_preview, theme_to_toml, Theme,
+};
+use crate::terminal::{detect_background, restore_terminal, ColorDepth};
 use crate::ui::ui;
 use clap::{CommandFactory, FromArgMatches};
+use o4t::clock::MonotonicClock;
+use o4t::game::{GameState, InputEvent};
+use o4t::generator::normalize_text;
 use etcetera::{choose_base_strategy, BaseStrategy};
 use figment::providers::Env;
 use figment::providers::{Format, Serialized, Toml};
 use figment::{Figment};
 use ratatui::backend::{Backend, CrosstermBackend};
-use ratatui::crossterm::event::{
-    DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers,
-};
-use ratatui::crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
-};
+use ratatui::crossterm::event::{EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::crossterm::terminal::{enable_raw_mode, EnterAlternateScreen};
 use ratatui::crossterm::{event, execute};
+use ratatui::layout::Rect;
 use ratatui::Terminal;
-use std::cmp::max;
 use std::error::Error;
+use std::io::{IsTerminal, Read};
+use std::path::Path;
+use std::{fs, process};
 use std::rc::Rc;
 use std::time::Instant;
-use std::{io, thread};
+use std::io;
 use tachyonfx::Duration;
-use tokio::sync::mpsc;
-use tokio::time::interval;
 
 mod app;
+mod book;
 mod theme;
 mod ui;
-mod words;
-mod wrap;
 mod cli;
 mod config;
+mod crash;
+mod fuzzy;
+mod history;
+mod keybindings;
+mod output;
+mod palette;
+mod terminal;
+#[cfg(test)]
+mod app_tests;
+#[cfg(test)]
+mod book_tests;
+#[cfg(test)]
+mod cli_tests;
+#[cfg(test)]
+mod history_tests;
+#[cfg(test)]
+mod keybindings_tests;
+#[cfg(test)]
+mod output_tests;
+#[cfg(test)]
+mod palette_tests;
+#[cfg(test)]
+mod terminal_tests;
+#[cfg(test)]
+mod theme_tests;
+#[cfg(test)]
+mod ui_tests;
+
+// How long the results are shown for before exiting, with `o4t run --once`.
+const ONCE_RESULTS_DURATION: std::time::Duration = std::time::Duration::from_secs(3);
+// Keys pressed this soon after the results appear were meant for the game, so they don't skip
+// the results.
+const ONCE_SKIP_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+// Exit codes for `o4t run`, when the last game failed or missed a target, or when the user
+// quit before finishing a game.
+const EXIT_TARGET_MISSED: i32 = 1;
+const EXIT_NO_GAME: i32 = 2;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let xdg = choose_base_strategy().unwrap();
     let config_file = xdg.config_dir().join("o4t/config.toml");
+    let history = History::new(xdg.data_dir().join("o4t/history.jsonl"));
     let mut cmd = Cli::command();
+    let themes_dir = xdg.config_dir().join("o4t/themes");
     let dynamic_help_text = format!(
-        "CONFIGURATION:\n    Config file: {}\n    Environment variables are prefixed with O4T_",
-        config_file.display()
+        "CONFIGURATION:\n    Config file: {}\n    Themes directory: {}\n    Environment variables are prefixed with O4T_",
+        config_file.display(),
+        themes_dir.display()
     );
     cmd = cmd.after_help(dynamic_help_text);
     let matches = cmd.get_matches_mut();
@@ -51,189 +98,515 @@ fn main() -> Result<(), Box<dyn Error>> {
             err.exit();
         }
     };
+    // `o4t run` and `o4t book` play the game, so they're handled below along with running o4t
+    // on its own.
+    let run_command = match &parsed_cli.command {
+        Some(Command::Run { once, min_wpm, target_accuracy }) => {
+            Some((*once, *min_wpm, *target_accuracy))
+        }
+        _ => None,
+    };
+    let books_dir = xdg.data_dir().join("o4t/books");
+    let book = match &parsed_cli.command {
+        Some(Command::Book { file, stats, restart }) => {
+            let book = open_book(file, parsed_cli.ascii, &books_dir, *restart);
+            let book = book.unwrap_or_else(|err| {
+                eprintln!("error: {}", err);
+                process::exit(1);
+            });
+            if *stats {
+                println!("{}", book.stats_table());
+                return Ok(());
+            }
+            Some(book)
+        }
+        _ => None,
+    };
+    if let Some(command) = &parsed_cli.command
+        && run_command.is_none()
+        && book.is_none()
+    {
+        let result = match command {
+            Command::Theme { command } => run_theme_command(command, &themes_dir),
+            Command::Themes => {
+                let themes = load_themes(&themes_dir);
+                let name_width = themes.iter().map(|theme| theme.name.len()).max().unwrap_or(0);
+                let color_depth = parsed_cli.colors.unwrap_or(ColorDepth::Auto).resolve();
+                for theme in &themes {
+                    println!("{}", theme_preview(theme, name_width, color_depth));
+                }
+                Ok(())
+            }
+            Command::Run { .. } | Command::Book { .. } => Ok(()),
+        };
+        if let Err(err) = result {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let output = parsed_cli.output;
+    // Read before the terminal is taken over. Keys are then read from the terminal itself,
+    // rather than stdin. Books have their own text, so nothing is read.
+    let text = match &book {
+        Some(_) => None,
+        None => read_text(parsed_cli.text.as_deref(), parsed_cli.ascii).unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }),
+    };
+    let file_figment = Figment::from(Toml::file(&config_file));
+    let env_figment = Figment::from(Env::prefixed("O4T_"));
+    let cli_figment = Figment::from(Serialized::defaults(parsed_cli));
     let config: Config = Figment::new()
         .merge(Serialized::defaults(Config::default()))
-        .merge(Toml::file(config_file))
-        .merge(Env::prefixed("O4T_"))
-        .merge(Serialized::defaults(parsed_cli))
-        .extract()?;
+        .merge(file_figment.clone())
+        .merge(env_figment.clone())
+        .merge(cli_figment.clone())
+        .extract()
+        .unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        });
+    let config_sources = settings(&[])
+        .iter()
+        .map(|setting| {
+            let source =
+                ConfigSource::of(setting.key, &file_figment, &env_figment, &cli_figment);
+            (setting.key.to_string(), source)
+        })
+        .collect();
+
+    // The background is only needed for "auto", and asking the terminal for it can be slow.
+    let background = if config.theme == AUTO_THEME {
+        detect_background()
+    } else {
+        None
+    };
 
-    let mut app = App::with_config(Rc::from(config));
+    if let Err(err) = config.keybindings.validate() {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+
+    // Check the theme exists before entering raw mode, so typos get a helpful message.
+    let themes = load_themes(&themes_dir);
+    if let Err(err) = find_theme(&themes, config.resolved_theme(background)) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+
+    crash::set_current_config(&config);
+    let reports_dir = xdg.state_dir().unwrap_or_else(|| xdg.data_dir()).join("o4t");
+    crash::install_panic_hook(reports_dir);
+
+    let clock = Rc::new(MonotonicClock::new());
+    let mut app = App::with_config(Rc::from(config), themes, clock)
+        .config_sources(config_sources)
+        .with_background(background);
+    if let Some(book) = book {
+        app = app.with_book(book);
+    } else if let Some(text) = text {
+        app = app.with_text(text);
+    }
 
     let mut stderr = io::stderr();
     execute!(stderr, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stderr);
     let mut terminal = Terminal::new(backend)?;
     enable_raw_mode()?;
-    let res = run_app(&mut terminal, &mut app);
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    let once = run_command.is_some_and(|(once, _, _)| once);
+    let res = run_app(&mut terminal, &mut app, &history, &config_file, once);
+    restore_terminal()?;
+
+    if let Err(err) = res {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+
+    // Quitting part way through a page keeps the words typed so far.
+    if let Some(book) = &mut app.book
+        && app.game.is_active()
+        && let Err(err) = book.record_game(&app.game)
+    {
+        eprintln!("error: couldn't save your place in the book: {}", err);
+    }
+
+    // Printed once the alternate screen has gone, so it can be piped into other programs.
+    if let Some(format) = output
+        && let Some(record) = &app.last_game
+    {
+        println!("{}", format.format(record)?);
+    }
 
-    if let Ok(do_print) = res {
-        if do_print {
-            // app.print_json()?;
+    if let Some((_, min_wpm, target_accuracy)) = run_command {
+        let Some(record) = &app.last_game else {
+            eprintln!("no game was finished");
+            process::exit(EXIT_NO_GAME);
+        };
+        if let Err(reason) = record.check_targets(min_wpm, target_accuracy) {
+            eprintln!("{}", reason);
+            process::exit(EXIT_TARGET_MISSED);
         }
-    } else if let Err(err) = res {
-        println!("{}", err);
     }
 
     Ok(())
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<bool> {
-    let (tx, mut rx) = mpsc::channel(100);
+/// Loads the built-in themes along with the user's themes, exiting if any theme file is invalid.
+fn load_themes(themes_dir: &Path) -> Vec<Theme> {
+    match load_theme_files(themes_dir) {
+        Ok(user_themes) => merge_themes(get_themes(), user_themes),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+fn run_theme_command(command: &ThemeCommand, themes_dir: &Path) -> Result<(), Box<dyn Error>> {
+    match command {
+        ThemeCommand::Import { file, name, force } => {
+            let format = PaletteFormat::detect(file).ok_or_else(|| {
+                format!(
+                    "couldn't tell what kind of colour scheme {} is, expected a base16 .yaml, an Alacritty .toml or a Kitty .conf file",
+                    file.display()
+                )
+            })?;
+            let mut theme = import_theme(file, format)?;
+            if let Some(name) = name {
+                theme.name = slugify(name);
+            }
+            if theme.name.is_empty() {
+                return Err("the theme needs a name made of letters or digits, use --name".into());
+            }
+            let theme_file = themes_dir.join(format!("{}.toml", theme.name));
+            if theme_file.exists() && !force {
+                return Err(format!(
+                    "{} already exists, use --force to overwrite it",
+                    theme_file.display()
+                )
+                .into());
+            }
+            fs::create_dir_all(themes_dir)?;
+            fs::write(&theme_file, theme_to_toml(&theme))?;
+            println!("Imported theme \"{}\" to {}", theme.name, theme_file.display());
+            Ok(())
+        }
+    }
+}
+
+/// Opens a book for `o4t book`, going back to its start if asked to.
+fn open_book(file: &Path, ascii: bool, books_dir: &Path, restart: bool) -> Result<Book, String> {
+    let error = |err: io::Error| format!("couldn't open {}: {}", file.display(), err);
+    let mut book = Book::open(file, ascii, books_dir).map_err(error)?;
+    if restart {
+        book.restart().map_err(error)?;
+    }
+    Ok(book)
+}
 
-    let _tokio_handle = thread::spawn(move || {
-        let rt = tokio::runtime::Builder::new_multi_thread()
-            .enable_all()
-            .build()
-            .unwrap();
-        rt.block_on(background_task(tx));
-    });
+/// Reads the text to type from `file`, or from stdin if the file is "-" or if text is being
+/// piped in. Returns `None` if there's no text, in which case words are generated as usual.
+fn read_text(file: Option<&Path>, ascii: bool) -> Result<Option<Rc<str>>, String> {
+    let read_stdin = || {
+        let mut bytes = Vec::new();
+        io::stdin()
+            .read_to_end(&mut bytes)
+            .map_err(|err| format!("couldn't read stdin: {}", err))?;
+        Ok::<_, String>(bytes)
+    };
+    let bytes = match file {
+        Some(file) if file == Path::new("-") => read_stdin()?,
+        Some(file) => fs::read(file)
+            .map_err(|err| format!("couldn't read {}: {}", file.display(), err))?,
+        None if !io::stdin().is_terminal() => read_stdin()?,
+        None => return Ok(None),
+    };
+    let text = normalize_text(&String::from_utf8_lossy(&bytes), ascii);
+    match file {
+        Some(file) if text.is_empty() => Err(format!("there are no words in {}", file.display())),
+        // Stdin might not be a terminal without text being piped in, e.g. when run from cron.
+        None if text.is_empty() => Ok(None),
+        _ => Ok(Some(Rc::from(text))),
+    }
+}
+
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    history: &History,
+    config_file: &Path,
+    once: bool,
+) -> io::Result<()> {
     terminal.clear()?;
 
     let mut last_frame_instant = Instant::now();
+    // When the results of the game were first shown, if we're only playing one game.
+    let mut once_results_shown_at = None;
     app.load_words_effect = load_words_effect(app.get_current_theme().clone());
     loop {
-        app.last_tick_duration = last_frame_instant.elapsed().into();
+        app.last_tick_duration = last_frame_instant.elapsed();
         last_frame_instant = Instant::now();
 
-        // The ui function will the frame and draw to it
-        terminal.draw(|f| ui(f, app))?;
+        app.game.tick();
+        end_game_if_finished(app, history);
 
-        if let Ok(_) = rx.try_recv() {
-            let last_tick_millis = app.last_tick_duration.as_millis() as u64;
-            app.current_millis = app.current_millis + last_tick_millis;
-            if app.game_time_remaining_millis() == 0 {
-                app.load_results_screen_effect = load_score_screen_effect();
-                app.game_active = false;
-                app.current_screen = Screen::Results;
-            }
-            if app.game_active {
-                app.refresh_internal_score();
-                if app.config.target_wpm > 0 {
-                    match app.ghost_offset {
-                        Some(current_ghost) => {
-                            let last_tick_secs = app.last_tick_duration.as_secs_f64();
-                            let target_chars_per_minute = 5 * app.config.target_wpm;
-                            let target_chars_per_second = target_chars_per_minute as f64 / 60.;
-                            let delta = target_chars_per_second * last_tick_secs;
-                            let next_ghost = current_ghost + delta;
-                            app.ghost_offset = Some(next_ghost);
-                        }
-                        None => {}
-                    }
-                }
+        if once && app.last_game.is_some() {
+            let shown_at = *once_results_shown_at.get_or_insert(app.clock.now());
+            if app.clock.now().saturating_sub(shown_at) >= ONCE_RESULTS_DURATION {
+                return Ok(());
             }
         }
 
+        // The ui function will the frame and draw to it
+        terminal.draw(|f| ui(f, app))?;
+
         if !event::poll(Duration::from_millis(32).into())? {
             continue;
         }
 
-        if let Event::Key(key) = event::read()? {
-            if key.kind == event::KeyEventKind::Release {
-                continue;
+        match event::read()? {
+            // Any key skips the rest of the results, once they've been up long enough to read.
+            Event::Key(key)
+                if once_results_shown_at.is_some() && key.kind != event::KeyEventKind::Release =>
+            {
+                let shown_for =
+                    once_results_shown_at.map(|shown_at| app.clock.now().saturating_sub(shown_at));
+                if shown_for >= Some(ONCE_SKIP_DELAY) {
+                    return Ok(());
+                }
             }
+            Event::Key(key) if handle_key(app, key, history, config_file)? => return Ok(()),
+            // Start again from a blank screen at the new size, so nothing from the old layout
+            // is left behind. The next frame lays everything out to fit.
+            Event::Resize(width, height) => terminal.resize(Rect::new(0, 0, width, height))?,
+            _ => {}
+        }
+    }
+}
 
-            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
-            let alt = key.modifiers.contains(KeyModifiers::ALT);
+/// Handles a key press, returning whether the user asked to quit.
+fn handle_key(
+    app: &mut App,
+    key: KeyEvent,
+    history: &History,
+    config_file: &Path,
+) -> io::Result<bool> {
+    if key.kind == event::KeyEventKind::Release {
+        return Ok(false);
+    }
+    app.error = None;
+
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+    // While the theme picker is open, it takes all input.
+    if let Some(picker) = &app.theme_picker {
+        match key.code {
+            KeyCode::Esc => {
+                app.close_theme_picker(false);
+            }
+            KeyCode::Enter => {
+                if let Some(theme_name) = app.close_theme_picker(true) {
+                    save_config_or_show_error(app, config_file, "theme", theme_name);
+                }
+            }
+            KeyCode::Up | KeyCode::BackTab => app.move_theme_picker_selection(-1),
+            KeyCode::Char('p' | 'k') if ctrl => app.move_theme_picker_selection(-1),
+            KeyCode::Down | KeyCode::Tab => app.move_theme_picker_selection(1),
+            KeyCode::Char('n' | 'j') if ctrl => app.move_theme_picker_selection(1),
+            KeyCode::Backspace => {
+                let mut filter = picker.filter.clone();
+                filter.pop();
+                app.set_theme_picker_filter(filter);
+            }
+            KeyCode::Char(char) if !ctrl && !alt => {
+                let filter = format!("{}{}", picker.filter, char);
+                app.set_theme_picker_filter(filter);
+            }
+            _ => {}
+        }
+        return Ok(false);
+    }
 
-            // Global bindings
-            match key.code {
-                KeyCode::Char('t') if ctrl => {
-                    app.next_theme();
-                    continue;
+    // Likewise for the command palette.
+    if let Some(palette) = &app.command_palette {
+        match key.code {
+            KeyCode::Esc => {
+                app.close_command_palette(false);
+            }
+            KeyCode::Enter => {
+                if let Some(action) = app.close_command_palette(true) {
+                    run_palette_action(app, action, history, config_file);
                 }
-                KeyCode::Esc => return Ok(true),
-                KeyCode::Tab => app.reset_game(),
-                _ => {}
-            }
-
-            // Screen-specific bindings
-            match app.current_screen {
-                Screen::Game => match key.code {
-                    // Pressing any character, while the game hasn't started, starts the game
-                    KeyCode::Char(' ') => {
-                        if !app.current_user_input.is_empty() {
-                            app.words[app.current_word_offset].user_attempt =
-                                app.current_user_input.clone();
-                            app.current_word_offset += 1;
-                            app.current_user_input = String::new();
-                        }
-                    }
-                    KeyCode::Char(char) => {
-                        if ctrl && char == 'w' {
-                            app.current_user_input = String::new();
-                            continue;
-                        }
-
-                        let current_word = &app.words[app.current_word_offset].word;
-                        let cursor_offset = app.current_user_input.len();
-                        let expected_char = current_word.chars().nth(cursor_offset);
-                        if let Some(expected_char) = expected_char {
-                            if char == expected_char {
-                                app.score.current_char_streak += 1;
-                                app.score.character_hits += 1;
-                            } else {
-                                app.score.current_char_streak = 0;
-                                app.score.character_misses += 1;
-                            }
-                        } else {
-                            // User has gone beyond the word and is typing extra characters.
-                            app.score.character_misses += 1;
-                            app.score.current_char_streak = 0;
-                        }
-                        app.score.best_char_streak =
-                            max(app.score.best_char_streak, app.score.current_char_streak);
-
-                        if !app.game_active {
-                            app.game_active = true;
-                            app.millis_at_current_game_start = app.current_millis;
-                        }
-                        app.current_user_input.push(char);
-                    }
-                    KeyCode::Backspace if app.game_active => {
-                        if ctrl || alt {
-                            app.current_user_input = String::new();
-                        }
-                        match app.current_user_input.pop() {
-                            Some(_) => {}
-                            None => {
-                                // Go back into the previous word if possible.
-                                if app.current_word_offset != 0
-                                    && app.words[app.current_word_offset - 1].user_attempt
-                                        != app.words[app.current_word_offset - 1].word
-                                {
-                                    app.current_word_offset -= 1;
-                                    app.current_user_input =
-                                        app.words[app.current_word_offset].user_attempt.clone();
-                                }
-                            }
-                        }
-                    }
-                    _ => {}
-                },
-                _ => {}
             }
+            KeyCode::Up | KeyCode::BackTab => app.move_command_palette_selection(-1),
+            KeyCode::Char('p' | 'k') if ctrl => app.move_command_palette_selection(-1),
+            KeyCode::Down | KeyCode::Tab => app.move_command_palette_selection(1),
+            KeyCode::Char('n' | 'j') if ctrl => app.move_command_palette_selection(1),
+            KeyCode::Backspace => {
+                let mut filter = palette.filter.clone();
+                filter.pop();
+                app.set_command_palette_filter(filter);
+            }
+            KeyCode::Char(char) if !ctrl && !alt => {
+                let filter = format!("{}{}", palette.filter, char);
+                app.set_command_palette_filter(filter);
+            }
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    if let Screen::Stats(_) = app.current_screen {
+        if key.code == KeyCode::Esc {
+            app.reset_game();
+        }
+        return Ok(false);
+    }
+
+    if let Screen::Settings = app.current_screen {
+        let steps = match key.code {
+            KeyCode::Esc => {
+                app.reset_game();
+                return Ok(false);
+            }
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => {
+                app.move_settings_selection(-1);
+                return Ok(false);
+            }
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => {
+                app.move_settings_selection(1);
+                return Ok(false);
+            }
+            KeyCode::Left | KeyCode::Char('h') => -1,
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter | KeyCode::Char(' ') => 1,
+            _ => return Ok(false),
+        };
+        if let Some((key, value)) = app.change_selected_setting(steps) {
+            save_config_or_show_error(app, config_file, key, value);
         }
+        return Ok(false);
     }
+
+    // Global bindings
+    let action = app.config.keybindings.action(&key);
+    match action {
+        Some(Action::ThemePicker) => {
+            app.open_theme_picker();
+            return Ok(false);
+        }
+        Some(Action::CommandPalette) => {
+            app.open_command_palette();
+            return Ok(false);
+        }
+        Some(Action::Settings) => {
+            app.open_settings();
+            return Ok(false);
+        }
+        Some(Action::Quit) => return Ok(true),
+        Some(Action::Restart) => {
+            app.reset_game();
+            return Ok(false);
+        }
+        _ => {}
+    }
+
+    // Screen-specific bindings
+    if let Screen::Game = app.current_screen {
+        let input = match (action, key.code) {
+            (Some(Action::Finish), _) => Some(InputEvent::Finish),
+            (Some(Action::DeleteWord), _) => Some(InputEvent::DeleteWord),
+            (_, KeyCode::Char(char)) => Some(InputEvent::Char(char)),
+            (_, KeyCode::Backspace) => Some(InputEvent::Backspace),
+            _ => None,
+        };
+        if let Some(input) = input {
+            app.game.handle_input(input);
+            end_game_if_finished(app, history);
+        }
+    }
+    Ok(false)
 }
 
-async fn background_task(tx: mpsc::Sender<u64>) {
-    let mut interval = interval(Duration::from_millis(32).into());
-    let mut millis_elapsed = 0u64;
-    loop {
-        interval.tick().await;
-        millis_elapsed += 50;
-        // If the receiver is dropped, the task will gracefully exit.
-        if tx.send(millis_elapsed).await.is_err() {
-            break;
+// Saves a changed setting to config.toml. The change has already been applied, so failing to
+// save it is shown in the footer rather than ending the session. So is a saved value that
+// won't be used next time, because an environment variable or CLI option overrides it.
+fn save_config_or_show_error(
+    app: &mut App,
+    config_file: &Path,
+    key: &str,
+    value: impl Into<toml_edit::Value>,
+) {
+    if let Err(err) = save_config_value(config_file, key, value) {
+        app.error = Some(format!("couldn't save {} to {}: {}", key, config_file.display(), err));
+        return;
+    }
+    let overridden_by = match app.config_source(key) {
+        ConfigSource::Env => format!("O4T_{}", key.to_uppercase()),
+        ConfigSource::Cli => format!("--{}", key.replace('_', "-")),
+        ConfigSource::Default | ConfigSource::File => return,
+    };
+    app.error = Some(format!("{} was saved, but {} overrides it", key, overridden_by));
+}
+
+fn run_palette_action(
+    app: &mut App,
+    action: PaletteAction,
+    history: &History,
+    config_file: &Path,
+) {
+    let (key, value) = match action {
+        PaletteAction::SetTime(secs) => ("time", SettingValue::Number(secs as i64)),
+        PaletteAction::SetMode(mode) => ("mode", app::value_setting(mode)),
+        PaletteAction::SetWordList(word_list) => ("word_list", app::value_setting(word_list)),
+        PaletteAction::TogglePunctuation => {
+            ("punctuation", SettingValue::Bool(!app.config.punctuation))
+        }
+        PaletteAction::ToggleGhost => (
+            "target_wpm",
+            SettingValue::Number(app.toggled_target_wpm() as i64),
+        ),
+        PaletteAction::PickTheme => {
+            app.open_theme_picker();
+            return;
+        }
+        PaletteAction::OpenSettings => {
+            app.open_settings();
+            return;
+        }
+        PaletteAction::OpenStats => {
+            match history.stats() {
+                Ok(stats) => app.current_screen = Screen::Stats(stats),
+                Err(err) => app.error = Some(format!("couldn't read the history: {}", err)),
+            }
+            return;
+        }
+    };
+    if app.set_config_value(key, value.clone()).is_some() {
+        app.reset_game();
+        save_config_or_show_error(app, config_file, key, value);
+    }
+}
+
+// Shows the results once the game is over, and records the game in the history.
+fn end_game_if_finished(app: &mut App, history: &History) {
+    if let Screen::Game = app.current_screen
+        && let GameState::Finished(_) = app.game.state()
+    {
+        app.show_results();
+        let record = GameRecord::from_app(app);
+        // Failing to save the history shouldn't end the session.
+        if let Err(err) = history.record(&record) {
+            app.error = Some(format!("couldn't save the game to the history: {}", err));
+        }
+        app.last_game = Some(record);
+        if let Some(book) = &mut app.book
+            && let Err(err) = book.record_game(&app.game)
+        {
+            app.error = Some(format!("couldn't save your place in the book: {}", err));
         }
     }
 }
@@ -0,0 +1,81 @@
+use crate::history::GameRecord;
+use clap::ValueEnum;
+use serde_json::Value;
+
+/// How the result of the last game is printed when o4t exits, for use in scripts.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+#[clap(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    // The same JSON object that's stored in the history file.
+    Json,
+    // A header row and a row of values.
+    Csv,
+    // One "name value" pair per line, for people.
+    Text,
+}
+
+// Every field of a GameRecord, in the order they're written to CSV. Fields which are missing
+// from a record (like fail_reason, for completed games) are left empty. New fields need adding
+// here too, which output_tests checks.
+pub const COLUMNS: [&str; 16] = [
+    "timestamp",
+    "mode",
+    "time",
+    "outcome",
+    "fail_reason",
+    "wpm",
+    "accuracy",
+    "character_matches",
+    "character_mismatches",
+    "character_hits",
+    "character_misses",
+    "chars_per_minute",
+    "real_words_per_minute",
+    "num_words",
+    "best_char_streak",
+    "current_char_streak",
+];
+
+impl OutputFormat {
+    pub fn format(&self, record: &GameRecord) -> serde_json::Result<String> {
+        let value = serde_json::to_value(record)?;
+        let field = |column: &str| value.get(column).map_or_else(String::new, value_to_string);
+        Ok(match self {
+            OutputFormat::Json => serde_json::to_string(record)?,
+            OutputFormat::Csv => {
+                let values = COLUMNS.map(|column| csv_escape(&field(column)));
+                format!("{}\n{}", COLUMNS.join(","), values.join(","))
+            }
+            OutputFormat::Text => {
+                let name_width = COLUMNS.iter().map(|column| column.len()).max().unwrap_or(0);
+                COLUMNS
+                    .iter()
+                    .filter(|column| value.get(**column).is_some())
+                    .map(|column| {
+                        // NaN scores (e.g. when nothing was typed) are serialised as null.
+                        let field = Some(field(column)).filter(|field| !field.is_empty());
+                        format!("{:<name_width$}  {}", column, field.as_deref().unwrap_or("-"))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        })
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(string) => string.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+pub fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
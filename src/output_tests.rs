@@ -0,0 +1,127 @@
+//! Tests for what scripts get from o4t: the last game printed with `--output`, and the targets
+//! checked by `o4t run`.
+
+use crate::history::{GameRecord, Outcome};
+use crate::output::{csv_escape, OutputFormat, COLUMNS};
+use o4t::game::{FailReason, Mode, Score};
+
+fn record(fail_reason: Option<FailReason>) -> GameRecord {
+    GameRecord {
+        timestamp: 1700000000,
+        mode: Mode::Time,
+        time: 30,
+        outcome: if fail_reason.is_some() {
+            Outcome::Failed
+        } else {
+            Outcome::Completed
+        },
+        fail_reason,
+        score: Score {
+            character_matches: 150,
+            character_mismatches: 2,
+            character_hits: 151,
+            character_misses: 3,
+            accuracy: 0.75,
+            chars_per_minute: 300.,
+            wpm: 61.5,
+            real_words_per_minute: 58.,
+            num_words: 29,
+            best_char_streak: 80,
+            current_char_streak: 12,
+        },
+    }
+}
+
+#[test]
+fn every_field_has_a_column() {
+    let value = serde_json::to_value(record(Some(FailReason::SuddenDeath))).unwrap();
+    let mut fields = value.as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+    let mut columns = COLUMNS.map(String::from).to_vec();
+    fields.sort();
+    columns.sort();
+    assert_eq!(fields, columns);
+}
+
+#[test]
+fn csv_has_a_header_and_a_row_of_values() {
+    let csv = OutputFormat::Csv.format(&record(None)).unwrap();
+    assert_eq!(
+        csv,
+        "timestamp,mode,time,outcome,fail_reason,wpm,accuracy,character_matches,\
+         character_mismatches,character_hits,character_misses,chars_per_minute,\
+         real_words_per_minute,num_words,best_char_streak,current_char_streak\n\
+         1700000000,time,30,completed,,61.5,0.75,150,2,151,3,300.0,58.0,29,80,12"
+    );
+
+    let csv = OutputFormat::Csv.format(&record(Some(FailReason::MinAccuracy))).unwrap();
+    let row = csv.lines().nth(1).unwrap();
+    assert!(row.starts_with("1700000000,time,30,failed,min-accuracy,61.5,"), "{}", row);
+}
+
+#[test]
+fn csv_fields_are_quoted_when_needed() {
+    assert_eq!(csv_escape("plain"), "plain");
+    assert_eq!(csv_escape("a,b"), "\"a,b\"");
+    assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    assert_eq!(csv_escape("two\nlines"), "\"two\nlines\"");
+}
+
+#[test]
+fn text_lists_each_field_on_its_own_line() {
+    let mut record = record(None);
+    record.score.wpm = f32::NAN;
+    let text = OutputFormat::Text.format(&record).unwrap();
+    let lines = text.lines().collect::<Vec<_>>();
+    // Completed games have no fail reason, so there's no line for it.
+    assert_eq!(lines.len(), COLUMNS.len() - 1);
+    assert_eq!(lines[0], "timestamp              1700000000");
+    assert_eq!(lines[3], "outcome                completed");
+    // NaN isn't a number in JSON, so it's shown as missing.
+    assert_eq!(lines[4], "wpm                    -");
+    assert_eq!(lines[14], "current_char_streak    12");
+}
+
+#[test]
+fn json_is_the_history_record() {
+    let record = record(Some(FailReason::SuddenDeath));
+    let json = OutputFormat::Json.format(&record).unwrap();
+    assert_eq!(json, serde_json::to_string(&record).unwrap());
+    assert!(json.contains("\"fail_reason\":\"sudden-death\""));
+}
+
+#[test]
+fn games_meeting_their_targets_pass() {
+    let record = record(None);
+    assert_eq!(record.check_targets(None, None), Ok(()));
+    assert_eq!(record.check_targets(Some(61.5), Some(75)), Ok(()));
+}
+
+#[test]
+fn games_missing_a_target_fail() {
+    let record = record(None);
+    assert_eq!(
+        record.check_targets(Some(70.), None),
+        Err("62 wpm is below the target of 70 wpm".to_string())
+    );
+    assert_eq!(
+        record.check_targets(Some(60.), Some(80)),
+        Err("75% accuracy is below the target of 80%".to_string())
+    );
+}
+
+#[test]
+fn games_with_nothing_typed_miss_every_target() {
+    let mut record = record(None);
+    record.score.wpm = f32::NAN;
+    record.score.accuracy = f32::NAN;
+    assert!(record.check_targets(Some(0.), None).is_err());
+    assert!(record.check_targets(None, Some(0)).is_err());
+    assert_eq!(record.check_targets(None, None), Ok(()));
+}
+
+#[test]
+fn failed_games_fail_even_without_targets() {
+    let record = record(Some(FailReason::SuddenDeath));
+    let reason = record.check_targets(None, None).unwrap_err();
+    assert!(reason.starts_with("the game failed: "), "{}", reason);
+}
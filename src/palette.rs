@@ -0,0 +1,271 @@
+use crate::theme::{Theme, ThemeError};
+use figment::providers::{Format, Toml};
+use figment::Figment;
+use ratatui::style::{Color, Style, Stylize};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The colour scheme formats which can be imported as themes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaletteFormat {
+    // A base16 scheme YAML file (base00 to base0F).
+    Base16,
+    // An Alacritty TOML config containing a `[colors]` table.
+    Alacritty,
+    // A Kitty config or theme file containing `foreground`, `background` and `colorN` lines.
+    Kitty,
+}
+
+impl PaletteFormat {
+    /// Guesses the format of a colour scheme file from its extension and, for TOML files,
+    /// whether it looks like an Alacritty config. Returns `None` for o4t theme files.
+    pub fn detect(path: &Path) -> Option<PaletteFormat> {
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+        match extension.as_str() {
+            "yaml" | "yml" => Some(PaletteFormat::Base16),
+            "conf" => Some(PaletteFormat::Kitty),
+            "toml" if Figment::from(Toml::file_exact(path)).contains("colors") => {
+                Some(PaletteFormat::Alacritty)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A terminal colour scheme: a foreground, a background and the 16 ANSI colours.
+#[derive(Debug, Default)]
+pub struct Palette {
+    pub name: Option<String>,
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    pub ansi: [Option<Color>; 16],
+}
+
+// The ANSI colour indices used when mapping a palette onto theme roles.
+const RED: usize = 1;
+const GREEN: usize = 2;
+const BLUE: usize = 4;
+const MAGENTA: usize = 5;
+
+/// Loads a colour scheme file as a theme, named after the scheme or, failing that, the file.
+pub fn import_theme(path: &Path, format: PaletteFormat) -> Result<Theme, ThemeError> {
+    let palette = Palette::load(path, format)?;
+    let file_name = path
+        .file_stem()
+        .map(|stem| slugify(&stem.to_string_lossy()))
+        .unwrap_or_default();
+    palette.to_theme(&file_name).map_err(|message| ThemeError {
+        path: path.to_path_buf(),
+        message,
+    })
+}
+
+impl Palette {
+    pub fn load(path: &Path, format: PaletteFormat) -> Result<Palette, ThemeError> {
+        let error = |message: String| ThemeError {
+            path: path.to_path_buf(),
+            message,
+        };
+        let palette = match format {
+            PaletteFormat::Alacritty => parse_alacritty(path).map_err(error)?,
+            PaletteFormat::Base16 | PaletteFormat::Kitty => {
+                let contents = fs::read_to_string(path).map_err(|err| error(err.to_string()))?;
+                if format == PaletteFormat::Base16 {
+                    parse_base16(&contents).map_err(error)?
+                } else {
+                    parse_kitty(&contents).map_err(error)?
+                }
+            }
+        };
+        Ok(palette)
+    }
+
+    /// Maps the palette onto theme roles. Falls back to `default_name` if the palette
+    /// doesn't name itself.
+    pub fn to_theme(&self, default_name: &str) -> Result<Theme, String> {
+        let required = |color: Option<Color>, role: &str| {
+            color.ok_or_else(|| format!("the colour scheme has no {} colour", role))
+        };
+        let fg = required(self.foreground, "foreground")?;
+        let bg = required(self.background, "background")?;
+        let red = required(self.ansi[RED], "red")?;
+        let green = required(self.ansi[GREEN], "green")?;
+        let blue = required(self.ansi[BLUE], "blue")?;
+        let magenta = required(self.ansi[MAGENTA], "magenta")?;
+        Ok(Theme {
+            name: self
+                .name
+                .as_deref()
+                .map_or_else(|| default_name.to_string(), slugify),
+            fg,
+            bg,
+            primary: blue,
+            secondary: magenta,
+            error: red,
+            success: green,
+            character_match: Style::default().fg(green).not_dim(),
+            character_mismatch: Style::default().fg(red),
+            character_upcoming: fg,
+            // Blending needs to know the actual colours, which we only have for RGB colours.
+            supports_alpha: matches!(bg, Color::Rgb(..)),
+        })
+    }
+}
+
+/// Turns a human-readable scheme name like "Tokyo Night" into a theme name like "tokyo-night".
+pub fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn parse_hex(value: &str) -> Result<Color, String> {
+    let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+    let hex = value
+        .strip_prefix('#')
+        .or_else(|| value.strip_prefix("0x"))
+        .unwrap_or(value);
+    Color::from_str(&format!("#{}", hex))
+        .map_err(|_| format!("invalid colour \"{}\", expected a hex code", value))
+}
+
+// base16 schemes are flat YAML (or nested under `palette:` in newer schemes), so
+// they're read line by line rather than with a full YAML parser.
+fn parse_base16(contents: &str) -> Result<Palette, String> {
+    let mut values = HashMap::new();
+    for line in contents.lines() {
+        let line = line.split(" #").next().unwrap_or_default().trim();
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+            if !value.is_empty() {
+                values.insert(key.trim().to_lowercase(), value.to_string());
+            }
+        }
+    }
+
+    let color = |key: &str| values.get(key).map(|value| parse_hex(value)).transpose();
+    let mut palette = Palette {
+        name: values.get("scheme").or_else(|| values.get("name")).cloned(),
+        foreground: color("base05")?,
+        background: color("base00")?,
+        ..Palette::default()
+    };
+    // The conventional mapping of base16 colours onto the ANSI palette.
+    let ansi_keys = [
+        (0, "base00"),
+        (1, "base08"),
+        (2, "base0b"),
+        (3, "base0a"),
+        (4, "base0d"),
+        (5, "base0e"),
+        (6, "base0c"),
+        (7, "base05"),
+        (8, "base03"),
+        (15, "base07"),
+    ];
+    for (index, key) in ansi_keys {
+        palette.ansi[index] = color(key)?;
+    }
+    Ok(palette)
+}
+
+fn parse_kitty(contents: &str) -> Result<Palette, String> {
+    let mut palette = Palette::default();
+    for line in contents.lines() {
+        let line = line.trim();
+        // Theme files from kitty-themes start with a "## name: <name>" comment.
+        if let Some(name) = line.strip_prefix("## name:") {
+            palette.name = Some(name.trim().to_string());
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(value)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        match key {
+            "foreground" => palette.foreground = Some(parse_hex(value)?),
+            "background" => palette.background = Some(parse_hex(value)?),
+            _ => {
+                if let Some(index) = key.strip_prefix("color")
+                    && let Ok(index) = index.parse::<usize>()
+                    && index < 16
+                {
+                    palette.ansi[index] = Some(parse_hex(value)?);
+                }
+            }
+        }
+    }
+    Ok(palette)
+}
+
+#[derive(Deserialize)]
+struct AlacrittyConfig {
+    colors: AlacrittyColors,
+}
+
+#[derive(Deserialize)]
+struct AlacrittyColors {
+    primary: Option<AlacrittyPrimary>,
+    normal: Option<AlacrittyAnsi>,
+    bright: Option<AlacrittyAnsi>,
+}
+
+#[derive(Deserialize)]
+struct AlacrittyPrimary {
+    foreground: Option<String>,
+    background: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AlacrittyAnsi {
+    black: Option<String>,
+    red: Option<String>,
+    green: Option<String>,
+    yellow: Option<String>,
+    blue: Option<String>,
+    magenta: Option<String>,
+    cyan: Option<String>,
+    white: Option<String>,
+}
+
+impl AlacrittyAnsi {
+    fn colors(&self) -> [&Option<String>; 8] {
+        [
+            &self.black,
+            &self.red,
+            &self.green,
+            &self.yellow,
+            &self.blue,
+            &self.magenta,
+            &self.cyan,
+            &self.white,
+        ]
+    }
+}
+
+fn parse_alacritty(path: &Path) -> Result<Palette, String> {
+    let config: AlacrittyConfig = Figment::from(Toml::file_exact(path))
+        .extract()
+        .map_err(|err| err.to_string())?;
+    let color = |value: &Option<String>| value.as_deref().map(parse_hex).transpose();
+
+    let mut palette = Palette::default();
+    if let Some(primary) = &config.colors.primary {
+        palette.foreground = color(&primary.foreground)?;
+        palette.background = color(&primary.background)?;
+    }
+    let ansi_sets = [(0, &config.colors.normal), (8, &config.colors.bright)];
+    for (offset, set) in ansi_sets {
+        if let Some(set) = set {
+            for (index, value) in set.colors().into_iter().enumerate() {
+                palette.ansi[offset + index] = color(value)?;
+            }
+        }
+    }
+    Ok(palette)
+}
@@ -0,0 +1,141 @@
+//! Tests for importing terminal colour schemes as themes, and writing them as theme files.
+
+use crate::palette::{import_theme, slugify, PaletteFormat};
+use crate::theme::{load_theme_file, theme_to_toml};
+use ratatui::style::{Color, Modifier};
+use std::path::PathBuf;
+use std::{env, fs, process};
+
+// Writes a scheme file into a directory of its own, which is emptied first.
+fn scheme_file(test: &str, file_name: &str, contents: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("o4t-palette-tests-{}-{}", process::id(), test));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(file_name);
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn rgb(hex: u32) -> Color {
+    Color::from_u32(hex)
+}
+
+#[test]
+fn base16_schemes_are_imported() {
+    let path = scheme_file(
+        "base16",
+        "tn.yaml",
+        "scheme: \"Tokyo Night\" # a comment\n\
+         author: \"someone\"\n\
+         base00: \"1a1b26\"\n\
+         base05: \"#a9b1d6\"\n\
+         base08: 'f7768e'\n\
+         base0B: \"9ece6a\"\n\
+         base0D: \"7aa2f7\"\n\
+         base0E: \"bb9af7\"\n",
+    );
+    assert_eq!(PaletteFormat::detect(&path), Some(PaletteFormat::Base16));
+    let theme = import_theme(&path, PaletteFormat::Base16).unwrap();
+    assert_eq!(theme.name, "tokyo-night");
+    assert_eq!(theme.fg, rgb(0xa9b1d6));
+    assert_eq!(theme.bg, rgb(0x1a1b26));
+    assert_eq!(theme.error, rgb(0xf7768e));
+    assert_eq!(theme.success, rgb(0x9ece6a));
+    assert_eq!(theme.primary, rgb(0x7aa2f7));
+    assert_eq!(theme.secondary, rgb(0xbb9af7));
+    assert!(theme.supports_alpha);
+}
+
+#[test]
+fn kitty_themes_are_imported() {
+    let mut contents = String::from("## name: Gruvbox Dark\n# comment\n\n");
+    contents += "foreground #ebdbb2\nbackground   #282828\n";
+    for index in 0..16 {
+        contents += &format!("color{} #0000{:02x}\n", index, index);
+    }
+    contents += "color16 #ffffff\ncursor #ffffff\n";
+    let path = scheme_file("kitty", "gruvbox.conf", &contents);
+
+    assert_eq!(PaletteFormat::detect(&path), Some(PaletteFormat::Kitty));
+    let theme = import_theme(&path, PaletteFormat::Kitty).unwrap();
+    assert_eq!(theme.name, "gruvbox-dark");
+    assert_eq!(theme.fg, rgb(0xebdbb2));
+    assert_eq!(theme.bg, rgb(0x282828));
+    assert_eq!(theme.error, rgb(0x01));
+    assert_eq!(theme.success, rgb(0x02));
+    assert_eq!(theme.primary, rgb(0x04));
+    assert_eq!(theme.secondary, rgb(0x05));
+}
+
+#[test]
+fn alacritty_configs_are_imported() {
+    let path = scheme_file(
+        "alacritty",
+        "Solarized Dark.toml",
+        "[colors.primary]\n\
+         foreground = \"#839496\"\n\
+         background = \"0x002b36\"\n\
+         [colors.normal]\n\
+         red = \"#dc322f\"\n\
+         green = \"#859900\"\n\
+         blue = \"#268bd2\"\n\
+         magenta = \"#d33682\"\n",
+    );
+    assert_eq!(PaletteFormat::detect(&path), Some(PaletteFormat::Alacritty));
+    // Alacritty configs don't name their colours, so the theme is named after the file.
+    let theme = import_theme(&path, PaletteFormat::Alacritty).unwrap();
+    assert_eq!(theme.name, "solarized-dark");
+    assert_eq!(theme.fg, rgb(0x839496));
+    assert_eq!(theme.bg, rgb(0x002b36));
+    assert_eq!(theme.error, rgb(0xdc322f));
+    assert_eq!(theme.primary, rgb(0x268bd2));
+}
+
+#[test]
+fn incomplete_or_invalid_schemes_are_rejected() {
+    let path = scheme_file("missing", "partial.conf", "foreground #ffffff\nbackground #000000\n");
+    let err = import_theme(&path, PaletteFormat::Kitty).unwrap_err();
+    assert!(err.message.contains("no red colour"), "{}", err.message);
+
+    let path = scheme_file("invalid", "bad.yaml", "base00: \"zzzzzz\"\n");
+    let err = import_theme(&path, PaletteFormat::Base16).unwrap_err();
+    assert!(err.message.contains("invalid colour"), "{}", err.message);
+
+    // o4t's own theme files aren't colour schemes.
+    let path = scheme_file("theme", "mine.toml", "name = \"mine\"\n");
+    assert_eq!(PaletteFormat::detect(&path), None);
+}
+
+#[test]
+fn names_are_slugified() {
+    assert_eq!(slugify("Tokyo Night (Storm)"), "tokyo-night-storm");
+    assert_eq!(slugify("../../etc/passwd"), "etc-passwd");
+    assert_eq!(slugify("--"), "");
+}
+
+#[test]
+fn themes_are_written_as_loadable_theme_files() {
+    let path = scheme_file(
+        "roundtrip",
+        "tn.yaml",
+        "base00: \"1a1b26\"\nbase05: \"a9b1d6\"\nbase08: \"f7768e\"\n\
+         base0B: \"9ece6a\"\nbase0D: \"7aa2f7\"\nbase0E: \"bb9af7\"\n",
+    );
+    let mut theme = import_theme(&path, PaletteFormat::Base16).unwrap();
+    // Names that need escaping in TOML, and indexed colours, survive the round trip.
+    theme.name = "a \"quoted\" \\ name\u{1b}".to_string();
+    theme.primary = Color::Indexed(33);
+    theme.character_match = theme.character_match.bg(Color::Reset).add_modifier(Modifier::BOLD);
+
+    let path = path.with_file_name("written.toml");
+    fs::write(&path, theme_to_toml(&theme)).unwrap();
+    let loaded = load_theme_file(&path).unwrap();
+    assert_eq!(loaded.name, theme.name);
+    assert_eq!(loaded.fg, theme.fg);
+    assert_eq!(loaded.primary, Color::Indexed(33));
+    assert_eq!(loaded.character_match, theme.character_match);
+    assert!(loaded.character_match.add_modifier.contains(Modifier::BOLD));
+    assert_eq!(loaded.character_mismatch, theme.character_mismatch);
+    assert_eq!(loaded.character_upcoming, theme.character_upcoming);
+    assert_eq!(loaded.supports_alpha, theme.supports_alpha);
+}
@@ -0,0 +1,329 @@
+use clap::ValueEnum;
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+use ratatui::crossterm::cursor::Show;
+use ratatui::crossterm::event::DisableMouseCapture;
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{disable_raw_mode, LeaveAlternateScreen};
+use serde::{Deserialize, Serialize};
+use std::{env, io};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Background {
+    Light,
+    Dark,
+}
+
+/// Puts the terminal back to normal after the game has taken it over.
+pub fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stderr(), LeaveAlternateScreen, DisableMouseCapture, Show)
+}
+
+/// Works out whether the terminal has a light or dark background. The terminal is asked for
+/// its background colour (OSC 11) first, falling back to the `COLORFGBG` environment variable
+/// which some terminals set.
+pub fn detect_background() -> Option<Background> {
+    query_background_color()
+        .map(|(r, g, b)| {
+            // Relative luminance, with each channel between 0 and 1.
+            let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+            if luminance > 0.5 {
+                Background::Light
+            } else {
+                Background::Dark
+            }
+        })
+        .or_else(|| background_from_colorfgbg(&env::var("COLORFGBG").ok()?))
+}
+
+// COLORFGBG looks like "15;0" (or "15;default;0"), where the last number is the ANSI colour
+// of the background.
+pub fn background_from_colorfgbg(value: &str) -> Option<Background> {
+    let bg = value.rsplit(';').next()?.parse::<u8>().ok()?;
+    // White, and the bright colours other than bright black, are light.
+    if bg == 7 || (9..=15).contains(&bg) {
+        Some(Background::Light)
+    } else {
+        Some(Background::Dark)
+    }
+}
+
+#[cfg(unix)]
+fn query_background_color() -> Option<(f32, f32, f32)> {
+    use ratatui::crossterm::terminal::enable_raw_mode;
+    use std::fs::OpenOptions;
+    use std::io::{Read, Write};
+    use std::os::fd::AsRawFd;
+    use std::time::{Duration, Instant};
+
+    // Terminals which don't support the query won't reply, so don't wait for long.
+    const TIMEOUT: Duration = Duration::from_millis(200);
+
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    // Raw mode stops the reply from being echoed, and lets us read it without a newline.
+    enable_raw_mode().ok()?;
+    let mut response = Vec::new();
+    if tty.write_all(b"\x1b]11;?\x1b\\").is_ok() && tty.flush().is_ok() {
+        let started = Instant::now();
+        let mut buffer = [0u8; 64];
+        while let Some(remaining) = TIMEOUT.checked_sub(started.elapsed()) {
+            let mut poll_fd = libc::pollfd {
+                fd: tty.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let ready = unsafe { libc::poll(&mut poll_fd, 1, remaining.as_millis() as i32) };
+            if ready <= 0 {
+                break;
+            }
+            match tty.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => response.extend_from_slice(&buffer[..read]),
+            }
+            // The reply is terminated by either BEL or ST (ESC \).
+            if response.ends_with(b"\x07") || response.ends_with(b"\x1b\\") {
+                break;
+            }
+        }
+    }
+    let _ = disable_raw_mode();
+    parse_osc11_response(&String::from_utf8_lossy(&response))
+}
+
+#[cfg(not(unix))]
+fn query_background_color() -> Option<(f32, f32, f32)> {
+    None
+}
+
+// The reply looks like "ESC ]11;rgb:ffff/ffff/ffff ESC \", where each channel has 1 to 4
+// hex digits.
+#[cfg_attr(not(unix), allow(dead_code))]
+pub fn parse_osc11_response(response: &str) -> Option<(f32, f32, f32)> {
+    let (_, color) = response.split_once("rgb:")?;
+    let mut channels = color.split('/').map(|channel| {
+        let digits = channel
+            .chars()
+            .take_while(|c| c.is_ascii_hexdigit())
+            .collect::<String>();
+        let value = u32::from_str_radix(&digits, 16).ok()?;
+        let max = 16u32.checked_pow(digits.len() as u32)? - 1;
+        Some(value as f32 / max as f32)
+    });
+    Some((channels.next()??, channels.next()??, channels.next()??))
+}
+
+/// How many colours the terminal can display.
+#[derive(ValueEnum, Clone, Debug, Copy, PartialEq, Serialize, Deserialize)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorDepth {
+    // Detect the colour depth from the environment.
+    Auto,
+    // 24-bit RGB colours.
+    Truecolor,
+    // The xterm 256 colour palette.
+    #[value(name = "256")]
+    #[serde(rename = "256")]
+    Ansi256,
+    // The 16 standard ANSI colours.
+    #[value(name = "16")]
+    #[serde(rename = "16")]
+    Ansi16,
+    // No colours at all, only modifiers like bold and reversed.
+    None,
+}
+
+impl ColorDepth {
+    /// Resolves `Auto` by looking at the environment: `NO_COLOR` disables colours,
+    /// `COLORTERM` advertises truecolor support, and `TERM` names the terminal's capabilities.
+    pub fn resolve(self) -> ColorDepth {
+        if self != ColorDepth::Auto {
+            return self;
+        }
+        ColorDepth::detect(
+            env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()),
+            &env::var("COLORTERM").unwrap_or_default(),
+            &env::var("TERM").unwrap_or_default(),
+        )
+    }
+
+    /// Works out the colour depth from the values of `NO_COLOR`, `COLORTERM` and `TERM`.
+    pub fn detect(no_color: bool, colorterm: &str, term: &str) -> ColorDepth {
+        if no_color {
+            return ColorDepth::None;
+        }
+        let colorterm = colorterm.to_lowercase();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorDepth::Truecolor;
+        }
+        let term = term.to_lowercase();
+        if term == "dumb" {
+            ColorDepth::None
+        } else if term.contains("direct") || term.contains("truecolor") {
+            ColorDepth::Truecolor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else if term.is_empty() {
+            // Windows terminals don't set TERM, but support truecolor.
+            if cfg!(windows) {
+                ColorDepth::Truecolor
+            } else {
+                ColorDepth::Ansi16
+            }
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+
+    /// Converts a colour to the closest colour that can be displayed at this colour depth.
+    pub fn adapt(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorDepth::Auto | ColorDepth::Truecolor, color) => color,
+            (ColorDepth::None, _) => Color::Reset,
+            (ColorDepth::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_256(r, g, b)),
+            (ColorDepth::Ansi16, Color::Rgb(r, g, b)) => nearest_16(r, g, b),
+            (ColorDepth::Ansi16, Color::Indexed(index)) if index >= 16 => {
+                let (r, g, b) = indexed_to_rgb(index);
+                nearest_16(r, g, b)
+            }
+            (_, color) => color,
+        }
+    }
+
+    /// Converts every colour in the buffer to this colour depth. Without colour, anything that
+    /// was highlighted with a background colour is shown reversed instead.
+    pub fn adapt_buffer(self, buffer: &mut Buffer, base_bg: Color) {
+        if matches!(self, ColorDepth::Auto | ColorDepth::Truecolor) {
+            return;
+        }
+        for cell in buffer.content.iter_mut() {
+            if self == ColorDepth::None && cell.bg != Color::Reset && cell.bg != base_bg {
+                cell.modifier.insert(Modifier::REVERSED);
+            }
+            cell.fg = self.adapt(cell.fg);
+            cell.bg = self.adapt(cell.bg);
+            cell.underline_color = self.adapt(cell.underline_color);
+        }
+    }
+}
+
+// The approximate RGB values of the 16 ANSI colours, in xterm's default palette.
+const ANSI_16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// The levels used by each channel of the 6x6x6 colour cube in the 256 colour palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let dr = r1 as i32 - r2 as i32;
+    let dg = g1 as i32 - g2 as i32;
+    let db = b1 as i32 - b2 as i32;
+    // Weighted towards green, which the eye is most sensitive to.
+    (2 * dr * dr + 4 * dg * dg + 3 * db * db) as u32
+}
+
+// Theme colours are often muted, and the closest of the 16 colours by distance alone would
+// often be grey. Instead, pick by hue and lightness, only using greys for unsaturated colours.
+pub fn nearest_16(r: u8, g: u8, b: u8) -> Color {
+    let max = r.max(g).max(b) as f32;
+    let min = r.min(g).min(b) as f32;
+    let lightness = (max + min) / 2.;
+    if max - min < 48. {
+        return match lightness as u8 {
+            0..=63 => Color::Black,
+            64..=159 => Color::DarkGray,
+            160..=223 => Color::Gray,
+            _ => Color::White,
+        };
+    }
+
+    let (r, g, b) = (r as f32, g as f32, b as f32);
+    let hue = if max == r {
+        60. * ((g - b) / (max - min)).rem_euclid(6.)
+    } else if max == g {
+        60. * ((b - r) / (max - min) + 2.)
+    } else {
+        60. * ((r - g) / (max - min) + 4.)
+    };
+    let light = lightness > 140.;
+    match (hue as u16, light) {
+        (0..=29 | 330..=360, false) => Color::Red,
+        (0..=29 | 330..=360, true) => Color::LightRed,
+        (30..=89, false) => Color::Yellow,
+        (30..=89, true) => Color::LightYellow,
+        (90..=149, false) => Color::Green,
+        (90..=149, true) => Color::LightGreen,
+        (150..=209, false) => Color::Cyan,
+        (150..=209, true) => Color::LightCyan,
+        (210..=269, false) => Color::Blue,
+        (210..=269, true) => Color::LightBlue,
+        (_, false) => Color::Magenta,
+        (_, true) => Color::LightMagenta,
+    }
+}
+
+// Picks the closest colour from the 6x6x6 colour cube (16-231) or the greyscale ramp (232-255).
+pub fn nearest_256(r: u8, g: u8, b: u8) -> u8 {
+    let nearest_level = |value: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&level| (CUBE_LEVELS[level] as i32 - value as i32).abs())
+            .unwrap_or(0)
+    };
+    let (r_level, g_level, b_level) = (nearest_level(r), nearest_level(g), nearest_level(b));
+    let cube_index = 16 + 36 * r_level + 6 * g_level + b_level;
+    let cube_rgb = (
+        CUBE_LEVELS[r_level],
+        CUBE_LEVELS[g_level],
+        CUBE_LEVELS[b_level],
+    );
+
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let grey_step = (average.saturating_sub(8) / 10).min(23) as u8;
+    let grey_value = 8 + grey_step * 10;
+    let grey_index = 232 + grey_step as usize;
+
+    if distance((grey_value, grey_value, grey_value), (r, g, b)) < distance(cube_rgb, (r, g, b)) {
+        grey_index as u8
+    } else {
+        cube_index as u8
+    }
+}
+
+pub fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI_16[index as usize].1,
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let value = 8 + (index - 232) * 10;
+            (value, value, value)
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Tests for working out the terminal's background and colour depth, and converting colours
+//! to fit it.
+
+use crate::terminal::{
+    background_from_colorfgbg, indexed_to_rgb, nearest_16, nearest_256, parse_osc11_response,
+    Background, ColorDepth,
+};
+use ratatui::style::Color;
+
+#[test]
+fn indexed_colours_have_rgb_values() {
+    assert_eq!(indexed_to_rgb(1), (205, 0, 0));
+    assert_eq!(indexed_to_rgb(15), (255, 255, 255));
+    assert_eq!(indexed_to_rgb(16), (0, 0, 0));
+    assert_eq!(indexed_to_rgb(67), (95, 135, 175));
+    assert_eq!(indexed_to_rgb(196), (255, 0, 0));
+    assert_eq!(indexed_to_rgb(232), (8, 8, 8));
+    assert_eq!(indexed_to_rgb(255), (238, 238, 238));
+}
+
+#[test]
+fn colours_in_the_256_colour_palette_are_hit_exactly() {
+    assert_eq!(nearest_256(255, 0, 0), 196);
+    assert_eq!(nearest_256(95, 135, 175), 67);
+    for index in 16..=255 {
+        let (r, g, b) = indexed_to_rgb(index);
+        assert_eq!(nearest_256(r, g, b), index, "{:?}", (r, g, b));
+    }
+}
+
+#[test]
+fn greys_use_the_greyscale_ramp() {
+    assert_eq!(nearest_256(128, 128, 128), 244);
+    assert_eq!(nearest_256(30, 30, 30), 234);
+    assert_eq!(nearest_256(240, 240, 240), 255);
+    // Slightly tinted greys are still closer to the ramp than to the colour cube.
+    assert_eq!(nearest_256(50, 52, 48), 236);
+    // Black and white are in the colour cube.
+    assert_eq!(nearest_256(0, 0, 0), 16);
+    assert_eq!(nearest_256(255, 255, 255), 231);
+}
+
+#[test]
+fn colours_fall_back_to_the_16_ansi_colours_by_hue() {
+    assert_eq!(nearest_16(205, 0, 0), Color::Red);
+    assert_eq!(nearest_16(0, 205, 0), Color::Green);
+    assert_eq!(nearest_16(0, 0, 238), Color::Blue);
+    assert_eq!(nearest_16(92, 92, 255), Color::LightBlue);
+    assert_eq!(nearest_16(128, 255, 255), Color::LightCyan);
+    // Muted theme colours keep their hue rather than turning grey.
+    assert_eq!(nearest_16(191, 97, 106), Color::LightRed);
+    assert_eq!(nearest_16(94, 129, 172), Color::Blue);
+    // Unsaturated colours are greys, picked by lightness.
+    assert_eq!(nearest_16(20, 20, 30), Color::Black);
+    assert_eq!(nearest_16(127, 127, 127), Color::DarkGray);
+    assert_eq!(nearest_16(200, 200, 190), Color::Gray);
+    assert_eq!(nearest_16(250, 250, 250), Color::White);
+}
+
+#[test]
+fn colours_are_adapted_to_the_colour_depth() {
+    let red = Color::Rgb(255, 0, 0);
+    assert_eq!(ColorDepth::Truecolor.adapt(red), red);
+    assert_eq!(ColorDepth::Ansi256.adapt(red), Color::Indexed(196));
+    assert_eq!(ColorDepth::Ansi16.adapt(red), Color::Red);
+    assert_eq!(ColorDepth::None.adapt(red), Color::Reset);
+
+    // Indexed colours beyond the first 16 are converted for 16 colour terminals.
+    assert_eq!(ColorDepth::Ansi256.adapt(Color::Indexed(244)), Color::Indexed(244));
+    assert_eq!(ColorDepth::Ansi16.adapt(Color::Indexed(244)), Color::DarkGray);
+    assert_eq!(ColorDepth::Ansi16.adapt(Color::Indexed(4)), Color::Indexed(4));
+    assert_eq!(ColorDepth::Ansi16.adapt(Color::Blue), Color::Blue);
+    assert_eq!(ColorDepth::Ansi16.adapt(Color::Reset), Color::Reset);
+}
+
+#[test]
+fn colour_depth_is_detected_from_the_environment() {
+    assert_eq!(ColorDepth::detect(false, "truecolor", "xterm"), ColorDepth::Truecolor);
+    assert_eq!(ColorDepth::detect(false, "24BIT", ""), ColorDepth::Truecolor);
+    assert_eq!(ColorDepth::detect(false, "", "xterm-direct"), ColorDepth::Truecolor);
+    assert_eq!(ColorDepth::detect(false, "", "xterm-256color"), ColorDepth::Ansi256);
+    assert_eq!(ColorDepth::detect(false, "", "screen"), ColorDepth::Ansi16);
+    assert_eq!(ColorDepth::detect(false, "", "dumb"), ColorDepth::None);
+    // NO_COLOR wins over everything else.
+    assert_eq!(ColorDepth::detect(true, "truecolor", "xterm-256color"), ColorDepth::None);
+    // Explicit colour depths aren't detected.
+    assert_eq!(ColorDepth::Ansi16.resolve(), ColorDepth::Ansi16);
+}
+
+#[test]
+fn background_colour_replies_are_parsed() {
+    // Replies can end with ST (ESC \) or BEL.
+    let white = parse_osc11_response("\x1b]11;rgb:ffff/ffff/ffff\x1b\\");
+    assert_eq!(white, Some((1., 1., 1.)));
+    let black = parse_osc11_response("\x1b]11;rgb:0000/0000/0000\x07");
+    assert_eq!(black, Some((0., 0., 0.)));
+    // Channels can have fewer digits.
+    let (r, g, b) = parse_osc11_response("\x1b]11;rgb:33/80/ff\x07").unwrap();
+    assert_eq!((r, g, b), (0.2, 128. / 255., 1.));
+}
+
+#[test]
+fn garbage_background_colour_replies_are_ignored() {
+    assert_eq!(parse_osc11_response(""), None);
+    assert_eq!(parse_osc11_response("\x1b[?1;2c"), None);
+    assert_eq!(parse_osc11_response("\x1b]11;rgb:ffff/ffff\x1b\\"), None);
+    assert_eq!(parse_osc11_response("\x1b]11;rgb:zz/00/00\x07"), None);
+}
+
+#[test]
+fn background_is_read_from_colorfgbg() {
+    assert_eq!(background_from_colorfgbg("15;0"), Some(Background::Dark));
+    assert_eq!(background_from_colorfgbg("0;15"), Some(Background::Light));
+    assert_eq!(background_from_colorfgbg("0;default;15"), Some(Background::Light));
+    assert_eq!(background_from_colorfgbg("0;7"), Some(Background::Light));
+    assert_eq!(background_from_colorfgbg("7;8"), Some(Background::Dark));
+    assert_eq!(background_from_colorfgbg("15;default"), None);
+    assert_eq!(background_from_colorfgbg(""), None);
+}
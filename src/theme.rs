@@ -1,14 +1,28 @@
-use ratatui::style::{Color, Style};
+use crate::palette::{import_theme, PaletteFormat};
+use crate::terminal::ColorDepth;
 use crate::ui::blend_colors;
+use figment::providers::{Format, Toml};
+use figment::Figment;
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use serde::Deserialize;
+use ratatui::crossterm::style::{Color as CColor, Stylize as _};
+use std::cmp::{max, min};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::{fmt, fs, io};
+use toml_edit::{Array, DocumentMut, InlineTable, Value};
 
 #[derive(Default, Clone, Debug)]
 pub struct Theme {
-    pub(crate) name: &'static str,
+    pub(crate) name: String,
     pub(crate) fg: Color,
     pub(crate) bg: Color,
     pub(crate) primary: Color,
     pub(crate) secondary: Color,
     pub(crate) error: Color,
+    #[allow(dead_code)]
     pub(crate) success: Color,
     pub(crate) character_match: Style,
     pub(crate) character_mismatch: Style,
@@ -21,4 +35,343 @@ impl Theme {
         blend_colors(self.secondary, self.bg, 0.3)
     }
 
+    /// The style of typed characters in blind mode, where correctness isn't shown.
+    pub fn character_blind(&self) -> Style {
+        Style::default().fg(self.primary).not_dim()
+    }
+
+}
+
+/// A problem loading the user's themes: a theme file that's invalid, or a themes directory that
+/// can't be read.
+#[derive(Debug)]
+pub struct ThemeError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl Display for ThemeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "couldn't load {}: {}", self.path.display(), self.message)
+    }
+}
+
+impl Error for ThemeError {}
+
+// A colour in a theme file. Either a string (a hex code like "#88C0D0", a name like
+// "light-blue", or "reset"), or an integer ANSI colour index from 0 to 255.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorDef {
+    Index(u8),
+    Name(String),
+}
+
+// A style in a theme file. Either just a foreground colour, or a table.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StyleDef {
+    Color(ColorDef),
+    Table {
+        fg: Option<ColorDef>,
+        bg: Option<ColorDef>,
+        #[serde(default)]
+        add_modifiers: Vec<String>,
+        #[serde(default)]
+        remove_modifiers: Vec<String>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    name: Option<String>,
+    fg: ColorDef,
+    bg: ColorDef,
+    primary: ColorDef,
+    secondary: ColorDef,
+    error: ColorDef,
+    success: ColorDef,
+    character_match: StyleDef,
+    character_mismatch: StyleDef,
+    character_upcoming: ColorDef,
+    supports_alpha: bool,
+}
+
+/// Loads every `*.toml` theme file in `dir`. A missing directory means there are no
+/// user themes, but a directory that can't be read or any malformed theme file is an error.
+pub fn load_theme_files(dir: &Path) -> Result<Vec<Theme>, ThemeError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => {
+            return Err(ThemeError {
+                path: dir.to_path_buf(),
+                message: err.to_string(),
+            })
+        }
+    };
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ["toml", "yaml", "yml", "conf"].contains(&&*ext.to_string_lossy()))
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| match PaletteFormat::detect(path) {
+            // Terminal colour schemes are converted to themes automatically.
+            Some(format) => import_theme(path, format),
+            None => load_theme_file(path),
+        })
+        .collect()
+}
+
+pub fn load_theme_file(path: &Path) -> Result<Theme, ThemeError> {
+    let error = |message: String| ThemeError {
+        path: path.to_path_buf(),
+        message,
+    };
+    let file: ThemeFile = Figment::from(Toml::file_exact(path))
+        .extract()
+        .map_err(|err| error(err.to_string()))?;
+    let color = |field: &str, def: ColorDef| {
+        parse_color(def).map_err(|msg| error(format!("`{}`: {}", field, msg)))
+    };
+    let style = |field: &str, def: StyleDef| {
+        parse_style(def).map_err(|msg| error(format!("`{}`: {}", field, msg)))
+    };
+
+    let name = match file.name {
+        Some(name) => name,
+        None => path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    };
+    if name.trim().is_empty() {
+        return Err(error("the theme name can't be empty".to_string()));
+    }
+
+    Ok(Theme {
+        name,
+        fg: color("fg", file.fg)?,
+        bg: color("bg", file.bg)?,
+        primary: color("primary", file.primary)?,
+        secondary: color("secondary", file.secondary)?,
+        error: color("error", file.error)?,
+        success: color("success", file.success)?,
+        character_match: style("character_match", file.character_match)?,
+        character_mismatch: style("character_mismatch", file.character_mismatch)?,
+        character_upcoming: color("character_upcoming", file.character_upcoming)?,
+        supports_alpha: file.supports_alpha,
+    })
+}
+
+/// Serializes a theme in the theme file format read by `load_theme_file`.
+pub fn theme_to_toml(theme: &Theme) -> String {
+    let color = |color: Color| -> Value {
+        match color {
+            Color::Indexed(index) => i64::from(index).into(),
+            color => color.to_string().into(),
+        }
+    };
+    let modifier_names = |modifier: Modifier| {
+        modifier
+            .iter_names()
+            .map(|(name, _)| name.to_lowercase().replace('_', "-"))
+            .collect::<Array>()
+    };
+    let style = |style: Style| -> Value {
+        let mut table = InlineTable::new();
+        if let Some(fg) = style.fg {
+            table.insert("fg", color(fg));
+        }
+        if let Some(bg) = style.bg {
+            table.insert("bg", color(bg));
+        }
+        if !style.add_modifier.is_empty() {
+            table.insert("add_modifiers", modifier_names(style.add_modifier).into());
+        }
+        if !style.sub_modifier.is_empty() {
+            table.insert("remove_modifiers", modifier_names(style.sub_modifier).into());
+        }
+        table.into()
+    };
+
+    let mut document = DocumentMut::new();
+    let fields = [
+        ("name", theme.name.as_str().into()),
+        ("fg", color(theme.fg)),
+        ("bg", color(theme.bg)),
+        ("primary", color(theme.primary)),
+        ("secondary", color(theme.secondary)),
+        ("error", color(theme.error)),
+        ("success", color(theme.success)),
+        ("character_upcoming", color(theme.character_upcoming)),
+        ("character_match", style(theme.character_match)),
+        ("character_mismatch", style(theme.character_mismatch)),
+        ("supports_alpha", theme.supports_alpha.into()),
+    ];
+    for (key, value) in fields {
+        document[key] = toml_edit::value(value);
+    }
+    document.to_string()
+}
+
+/// The user asked for a theme which doesn't exist.
+#[derive(Debug)]
+pub struct UnknownThemeError {
+    pub name: String,
+    pub suggestion: Option<String>,
+    pub available: Vec<String>,
+}
+
+impl Display for UnknownThemeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown theme \"{}\"", self.name)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, ", did you mean \"{}\"?", suggestion)?;
+        }
+        write!(f, "\navailable themes: {}", self.available.join(", "))
+    }
+}
+
+impl Error for UnknownThemeError {}
+
+/// Looks up a theme by name, ignoring case if no theme matches exactly, and suggesting the
+/// closest match if it doesn't exist.
+pub fn find_theme<'a>(themes: &'a [Theme], name: &str) -> Result<&'a Theme, UnknownThemeError> {
+    let theme = themes
+        .iter()
+        .find(|theme| theme.name == name)
+        .or_else(|| themes.iter().find(|theme| theme.name.eq_ignore_ascii_case(name)));
+    if let Some(theme) = theme {
+        return Ok(theme);
+    }
+    let suggestion = themes
+        .iter()
+        .map(|theme| (edit_distance(&theme.name, name), &theme.name))
+        .filter(|(distance, theme_name)| *distance <= max(2, theme_name.len() / 3))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, theme_name)| theme_name.to_string());
+    Err(UnknownThemeError {
+        name: name.to_string(),
+        suggestion,
+        available: themes.iter().map(|theme| theme.name.to_string()).collect(),
+    })
+}
+
+// The Levenshtein distance between two strings.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(min(substitution, min(previous[j + 1], current[j]) + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Renders a one-line preview of a theme for printing to the terminal: its name, a swatch
+/// of its colours, and some sample text.
+pub fn theme_preview(theme: &Theme, name_width: usize, color_depth: ColorDepth) -> String {
+    let color = |color: Color| CColor::from(color_depth.adapt(color));
+    let swatch = [
+        theme.bg,
+        theme.fg,
+        theme.primary,
+        theme.secondary,
+        theme.success,
+        theme.error,
+        theme.character_upcoming,
+    ]
+    .iter()
+    .map(|swatch_color| "   ".on(color(*swatch_color)).to_string())
+    .collect::<String>();
+    let fg = color(theme.fg);
+    let bg = color(theme.bg);
+    let sample = format!(
+        "{}{}{}",
+        " o4t ".with(color(theme.primary)).on(bg).bold(),
+        "the quick ".with(fg).on(bg),
+        "brwon ".with(color(theme.error)).on(bg),
+    );
+    format!("{:width$}  {} {}", theme.name, swatch, sample, width = name_width)
+}
+
+/// Adds user themes to the built-in themes. A user theme with the same name as a
+/// built-in theme replaces it.
+pub fn merge_themes(builtin: Vec<Theme>, user: Vec<Theme>) -> Vec<Theme> {
+    let mut themes = builtin;
+    for theme in user {
+        match themes.iter_mut().find(|existing| existing.name == theme.name) {
+            Some(existing) => *existing = theme,
+            None => themes.push(theme),
+        }
+    }
+    themes
+}
+
+fn parse_color(def: ColorDef) -> Result<Color, String> {
+    match def {
+        ColorDef::Index(index) => Ok(Color::Indexed(index)),
+        ColorDef::Name(name) => Color::from_str(&name).map_err(|_| {
+            format!(
+                "invalid colour \"{}\", expected a hex code like \"#88C0D0\", a colour name like \"light-blue\", or an ANSI colour index from 0 to 255",
+                name
+            )
+        }),
+    }
+}
+
+fn parse_style(def: StyleDef) -> Result<Style, String> {
+    match def {
+        StyleDef::Color(color) => Ok(Style::default().fg(parse_color(color)?)),
+        StyleDef::Table {
+            fg,
+            bg,
+            add_modifiers,
+            remove_modifiers,
+        } => {
+            let mut style = Style::default();
+            if let Some(fg) = fg {
+                style = style.fg(parse_color(fg)?);
+            }
+            if let Some(bg) = bg {
+                style = style.bg(parse_color(bg)?);
+            }
+            for modifier in add_modifiers {
+                style = style.add_modifier(parse_modifier(&modifier)?);
+            }
+            for modifier in remove_modifiers {
+                style = style.remove_modifier(parse_modifier(&modifier)?);
+            }
+            Ok(style)
+        }
+    }
+}
+
+fn parse_modifier(name: &str) -> Result<Modifier, String> {
+    match name.to_lowercase().replace('_', "-").as_str() {
+        "bold" => Ok(Modifier::BOLD),
+        "dim" => Ok(Modifier::DIM),
+        "italic" => Ok(Modifier::ITALIC),
+        "underlined" => Ok(Modifier::UNDERLINED),
+        "slow-blink" => Ok(Modifier::SLOW_BLINK),
+        "rapid-blink" => Ok(Modifier::RAPID_BLINK),
+        "reversed" => Ok(Modifier::REVERSED),
+        "hidden" => Ok(Modifier::HIDDEN),
+        "crossed-out" => Ok(Modifier::CROSSED_OUT),
+        _ => Err(format!(
+            "unknown modifier \"{}\", expected one of bold, dim, italic, underlined, slow-blink, rapid-blink, reversed, hidden or crossed-out",
+            name
+        )),
+    }
 }
@@ -0,0 +1,145 @@
+//! Tests for loading the user's theme files, and looking themes up by name.
+
+use crate::app::get_themes;
+use crate::theme::{edit_distance, find_theme, load_theme_file, load_theme_files};
+use ratatui::style::{Color, Modifier};
+use std::path::PathBuf;
+use std::{env, fs, process};
+
+const THEME: &str = r##"
+name = "mine"
+fg = "#d8dee9"
+bg = "black"
+primary = 4
+secondary = "light-magenta"
+error = "#bf616a"
+success = "green"
+character_match = { fg = "#d8dee9", add_modifiers = ["bold"] }
+character_mismatch = "red"
+character_upcoming = "dark-gray"
+supports_alpha = true
+"##;
+
+// An empty directory of its own for each test.
+fn themes_dir(test: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("o4t-theme-tests-{}-{}", process::id(), test));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Writes a theme file, with `replace` applied to the valid theme above.
+fn theme_file(test: &str, replace: (&str, &str)) -> PathBuf {
+    let path = themes_dir(test).join("mine.toml");
+    fs::write(&path, THEME.replace(replace.0, replace.1)).unwrap();
+    path
+}
+
+#[test]
+fn theme_files_are_loaded() {
+    let theme = load_theme_file(&theme_file("valid", ("", ""))).unwrap();
+    assert_eq!(theme.name, "mine");
+    assert_eq!(theme.fg, Color::Rgb(0xd8, 0xde, 0xe9));
+    assert_eq!(theme.bg, Color::Black);
+    assert_eq!(theme.primary, Color::Indexed(4));
+    assert_eq!(theme.secondary, Color::LightMagenta);
+    assert_eq!(theme.character_match.add_modifier, Modifier::BOLD);
+    assert_eq!(theme.character_mismatch.fg, Some(Color::Red));
+    assert!(theme.supports_alpha);
+
+    // Themes without a name are named after the file.
+    let theme = load_theme_file(&theme_file("unnamed", ("name = \"mine\"", ""))).unwrap();
+    assert_eq!(theme.name, "mine");
+}
+
+#[test]
+fn theme_files_with_missing_or_unknown_keys_are_rejected() {
+    let path = theme_file("missing", ("primary = 4", ""));
+    let err = load_theme_file(&path).unwrap_err();
+    assert_eq!(err.path, path);
+    assert!(err.message.contains("missing field `primary`"), "{}", err.message);
+
+    let err = load_theme_file(&theme_file("unknown", ("primary", "primray"))).unwrap_err();
+    assert!(err.message.contains("unknown field: found `primray`"), "{}", err.message);
+
+    let err = load_theme_file(&theme_file("empty-name", ("\"mine\"", "\" \""))).unwrap_err();
+    assert_eq!(err.message, "the theme name can't be empty");
+}
+
+#[test]
+fn theme_files_with_bad_colours_are_rejected() {
+    let err = load_theme_file(&theme_file("bad-hex", ("#bf616a", "#bf61"))).unwrap_err();
+    assert!(err.message.starts_with("`error`: invalid colour \"#bf61\""), "{}", err.message);
+
+    let err = load_theme_file(&theme_file("bad-name", ("\"green\"", "\"grene\""))).unwrap_err();
+    assert!(err.message.starts_with("`success`: invalid colour"), "{}", err.message);
+
+    let err = load_theme_file(&theme_file("bad-index", ("= 4", "= 256"))).unwrap_err();
+    assert!(err.message.contains("primary"), "{}", err.message);
+
+    let path = theme_file("bad-modifier", ("\"bold\"", "\"bolder\""));
+    let err = load_theme_file(&path).unwrap_err();
+    assert!(err.message.starts_with("`character_match`: unknown modifier"), "{}", err.message);
+    assert!(err.to_string().starts_with(&format!("couldn't load {}", path.display())));
+}
+
+#[test]
+fn a_missing_themes_directory_has_no_themes() {
+    let dir = themes_dir("missing-dir").join("themes");
+    assert_eq!(load_theme_files(&dir).unwrap().len(), 0);
+}
+
+#[test]
+fn only_theme_files_in_the_themes_directory_are_loaded() {
+    let dir = theme_file("dir", ("", "")).parent().unwrap().to_path_buf();
+    fs::write(dir.join("notes.txt"), "not a theme").unwrap();
+    let themes = load_theme_files(&dir).unwrap();
+    assert_eq!(themes.len(), 1);
+    assert_eq!(themes[0].name, "mine");
+
+    fs::write(dir.join("broken.toml"), "fg = ").unwrap();
+    let err = load_theme_files(&dir).unwrap_err();
+    assert_eq!(err.path, dir.join("broken.toml"));
+}
+
+#[test]
+fn unreadable_themes_directories_are_an_error() {
+    // A file where the directory should be can't be read as one.
+    let path = theme_file("not-a-dir", ("", ""));
+    let err = load_theme_files(&path).unwrap_err();
+    assert_eq!(err.path, path);
+}
+
+#[test]
+fn themes_are_found_by_name() {
+    let themes = get_themes();
+    assert_eq!(find_theme(&themes, "nord").unwrap().name, "nord");
+    assert_eq!(find_theme(&themes, "Nord").unwrap().name, "nord");
+    assert_eq!(find_theme(&themes, "CATPPUCCIN-Latte").unwrap().name, "catppuccin-latte");
+}
+
+#[test]
+fn unknown_themes_suggest_close_matches() {
+    let themes = get_themes();
+    let err = find_theme(&themes, "nrod").unwrap_err();
+    assert_eq!(err.suggestion.as_deref(), Some("nord"));
+    assert_eq!(err.available.len(), themes.len());
+    assert!(err.to_string().starts_with("unknown theme \"nrod\", did you mean \"nord\"?\n"));
+
+    let err = find_theme(&themes, "catppucin-moca").unwrap_err();
+    assert_eq!(err.suggestion.as_deref(), Some("catppuccin-mocha"));
+
+    // Names too far from any theme get no suggestion.
+    let err = find_theme(&themes, "solarized").unwrap_err();
+    assert_eq!(err.suggestion, None);
+    assert!(err.to_string().starts_with("unknown theme \"solarized\"\navailable themes: "));
+}
+
+#[test]
+fn edit_distance_counts_single_character_edits() {
+    assert_eq!(edit_distance("nord", "nord"), 0);
+    assert_eq!(edit_distance("nord", "nrod"), 2);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "abc"), 3);
+    assert_eq!(edit_distance("café", "cafe"), 1);
+}
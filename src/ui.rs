@@ -1,8 +1,8 @@
-use crate::app::{App, CurrentWord, CursorType, Screen};
+use crate::app::{App, CurrentWord, CursorType, ErrorIndicator, Screen};
+use crate::keybindings::Action;
 use crate::theme::Theme;
-use crate::wrap::{LineComposer, WordWrapper};
+use o4t::wrap::{LineComposer, WordWrapper};
 use ratatui::buffer::Buffer;
-use ratatui::layout::Constraint::Max;
 use ratatui::layout::Flex::Center;
 use ratatui::layout::{Alignment, Margin, Offset};
 use ratatui::prelude::{Line, Widget};
@@ -25,6 +25,18 @@ use tachyonfx::{EffectRenderer, Shader, ToRgbComponents};
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+// Drawn in place of each upcoming character when words are hidden in memory mode.
+const MEMORY_MASK_SYMBOL: &str = "·";
+
+// Below this size there's no room to play, so a message is shown instead.
+const MIN_WIDTH: u16 = 20;
+const MIN_HEIGHT: u16 = 6;
+// Below this size, margins and padding shrink to leave more room for the words.
+const COMPACT_WIDTH: u16 = 60;
+const COMPACT_HEIGHT: u16 = 14;
+// The number of lines of words shown while playing, when there's room for them.
+const TEXT_ROWS: u16 = 6;
+
 #[derive(Default, Debug)]
 struct ResultData {
     pub value: String,
@@ -53,24 +65,33 @@ pub fn ui(screen_frame: &mut Frame, app: &mut App) {
         .fg(current_theme.fg)
         .bg(current_theme.bg)
         .render(screen_frame.area(), screen_frame.buffer_mut());
+    let area = screen_frame.area();
+    if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+        build_too_small_screen(screen_frame, app);
+        app.color_depth
+            .adapt_buffer(screen_frame.buffer_mut(), current_theme.bg);
+        return;
+    }
     match app.current_screen {
         Screen::Game => build_game_screen(screen_frame, app),
-        Screen::Results => build_score_screen(screen_frame, app),
+        Screen::Results | Screen::Failed(_) => build_score_screen(screen_frame, app),
+        Screen::Settings => build_settings_screen(screen_frame, app),
+        Screen::Stats(_) => build_stats_screen(screen_frame, app),
+    }
+    if app.theme_picker.is_some() {
+        build_theme_picker(screen_frame, app);
+    }
+    if app.command_palette.is_some() {
+        build_command_palette(screen_frame, app);
     }
+    app.color_depth
+        .adapt_buffer(screen_frame.buffer_mut(), current_theme.bg);
 }
 
 fn build_game_screen(screen_frame: &mut Frame, app: &mut App) {
     let current_theme = app.get_current_theme();
-    let screen_sections = Layout::default()
-        .horizontal_margin(3)
-        .vertical_margin(1)
-        .direction(Direction::Vertical)
-        .constraints([
-            Length(1), // Header
-            Min(7),    // Body (timer row, plus text)
-            Length(1), // Footer
-        ])
-        .split(screen_frame.area());
+    let compact = is_compact(screen_frame.area());
+    let screen_sections = screen_sections(screen_frame.area());
 
     // Header (actual render call is at bottom since we may need to include debug info).
     if app.is_debug_mode {
@@ -84,80 +105,78 @@ fn build_game_screen(screen_frame: &mut Frame, app: &mut App) {
 
     // Body text containing the words to show the user that they must type.
     let words = app
-        .words
+        .game
+        .words()
         .iter()
         .map(|word_attempt| word_attempt.word.clone())
         .collect::<Vec<_>>();
 
+    let ghost_offset = app.ghost_offset();
     let mut words_text = Text::default();
     let mut cursor_offset = 0;
     let mut expected_char_offset = 0;
 
     for (index, word) in words.iter().enumerate() {
-        let mut char_style = Style::default().fg(current_theme.fg);
-        let user_attempt = &app.words[index].user_attempt;
+        let char_style = Style::default().fg(current_theme.fg);
+        let user_attempt = &app.game.words()[index].user_attempt;
 
         let expected_word_num_graphemes = word.graphemes(false).count();
 
         // Compute the cursor offset
-        if index < app.current_word_offset {
+        if index < app.game.current_word_offset() {
             cursor_offset += max(
-                app.words[index].user_attempt.graphemes(false).count(),
+                app.game.words()[index].user_attempt.graphemes(false).count(),
                 expected_word_num_graphemes,
             );
-        } else if index == app.current_word_offset {
-            let user_input_num_graphemes = app.current_user_input.graphemes(false).count();
+        } else if index == app.game.current_word_offset() {
+            let user_input_num_graphemes = app.game.current_user_input().graphemes(false).count();
             cursor_offset += user_input_num_graphemes;
         }
 
         // The ghost offset should ignore current user input and user attempts, and only look
-        // at the words the user is expected to type. +1 for space.
-        expected_char_offset += expected_word_num_graphemes + 1;
+        // at the words the user is expected to type. +1 for space. Like the score, it counts
+        // chars, and so does the offset of the ghost cursor within the word.
+        let expected_word_num_chars = word.chars().count();
+        expected_char_offset += expected_word_num_chars + 1;
         let mut ghost_cursor_word_offset = None;
-        match app.ghost_offset {
-            None => {}
-            Some(app_ghost_offset) => {
-                if expected_char_offset >= (app_ghost_offset as usize)
-                    && app_ghost_offset as usize
-                        >= expected_char_offset - expected_word_num_graphemes
-                {
-                    // The ghost cursor is within this word.
-                    let offset_from_end_of_word =
-                        expected_char_offset - (app_ghost_offset as usize);
-                    ghost_cursor_word_offset =
-                        Some(expected_word_num_graphemes.saturating_sub(offset_from_end_of_word));
-                    app.debug_string = format!(
-                        "{:.2}, {:.2}",
-                        app.ghost_offset.unwrap_or(0.0),
-                        ghost_cursor_word_offset.unwrap_or(0)
-                    );
-                }
-            }
+        if let Some(app_ghost_offset) = ghost_offset
+            && expected_char_offset >= (app_ghost_offset as usize)
+            && app_ghost_offset as usize >= expected_char_offset - expected_word_num_chars
+        {
+            // The ghost cursor is within this word.
+            let offset_from_end_of_word = expected_char_offset - (app_ghost_offset as usize);
+            ghost_cursor_word_offset =
+                Some(expected_word_num_chars.saturating_sub(offset_from_end_of_word));
+            app.debug_string = format!(
+                "{:.2}, {:.2}",
+                app_ghost_offset,
+                ghost_cursor_word_offset.unwrap_or(0)
+            );
         }
 
-        if app.current_word_offset == index {
+        if app.game.current_word_offset() == index {
             // Check which characters match and which ones don't in order to build up the styling for this word.
             build_styled_word(
                 app,
                 &mut words_text,
                 char_style,
-                app.current_user_input.to_string(),
+                app.game.current_user_input().to_string(),
                 word.to_string(),
                 true,
                 false,
                 ghost_cursor_word_offset,
             );
-            if app.current_user_input.len() >= word.len() {
+            if app.game.current_user_input().chars().count() >= expected_word_num_chars {
                 words_text.push_span(Span::styled(
                     " ",
                     Style::default().patch(cursor_type_to_ratatui_style(&app.cursor_style, app)),
                 ))
             } else {
                 let mut space = Span::default().content(" ");
-                if let Some(ghost_cursor_word_offset) = ghost_cursor_word_offset {
-                    if ghost_cursor_word_offset == word.len() {
-                        space = space.bg(current_theme.ghost_cursor_color())
-                    }
+                if let Some(ghost_cursor_word_offset) = ghost_cursor_word_offset
+                    && ghost_cursor_word_offset == expected_word_num_chars
+                {
+                    space = space.bg(current_theme.ghost_cursor_color())
                 }
                 words_text.push_span(space);
             }
@@ -166,22 +185,23 @@ fn build_game_screen(screen_frame: &mut Frame, app: &mut App) {
             // Isolate the ghost cursor character
             if let Some(ghost_cursor_word_offset) = ghost_cursor_word_offset {
                 let ghost_cursor_char = word.chars().nth(ghost_cursor_word_offset);
-                let (before, after) = word.split_at(ghost_cursor_word_offset);
+                let before = word.chars().take(ghost_cursor_word_offset).collect::<String>();
+                let after = word.chars().skip(ghost_cursor_word_offset + 1).collect::<String>();
 
                 words_text.push_span(Span::styled(
                     before,
                     char_style.patch(current_theme.character_upcoming),
                 ));
-                ghost_cursor_char.map(|ghost_cursor_char| {
+                if let Some(ghost_cursor_char) = ghost_cursor_char {
                     words_text.push_span(Span::styled(
                         ghost_cursor_char.to_string(),
                         char_style
                             .patch(current_theme.character_upcoming)
                             .bg(current_theme.ghost_cursor_color()),
                     ));
-                });
+                }
                 words_text.push_span(Span::styled(
-                    after.chars().skip(1).collect::<String>(),
+                    after,
                     char_style.patch(current_theme.character_upcoming),
                 ));
             } else {
@@ -191,10 +211,10 @@ fn build_game_screen(screen_frame: &mut Frame, app: &mut App) {
             }
             let mut space = Span::default().content(" ");
             if index != words.len() - 1 {
-                if let Some(ghost_cursor_word_offset) = ghost_cursor_word_offset {
-                    if ghost_cursor_word_offset == word.len() {
-                        space = space.bg(current_theme.ghost_cursor_color())
-                    }
+                if let Some(ghost_cursor_word_offset) = ghost_cursor_word_offset
+                    && ghost_cursor_word_offset == expected_word_num_chars
+                {
+                    space = space.bg(current_theme.ghost_cursor_color())
                 }
                 words_text.push_span(space);
             }
@@ -212,49 +232,55 @@ fn build_game_screen(screen_frame: &mut Frame, app: &mut App) {
             );
             if index != words.len() - 1 {
                 let mut space = Span::default().content(" ");
-                if let Some(ghost_cursor_word_offset) = ghost_cursor_word_offset {
-                    if ghost_cursor_word_offset == word.len() {
-                        space = space.bg(current_theme.ghost_cursor_color())
-                    }
+                if let Some(ghost_cursor_word_offset) = ghost_cursor_word_offset
+                    && ghost_cursor_word_offset == expected_word_num_chars
+                {
+                    space = space.bg(current_theme.ghost_cursor_color())
                 }
                 words_text.push_span(space);
             }
         }
     }
 
-    // The body has 2 rows - a single cell height row for the timer, and 5 rows for the text to type
+    // The body has a single row for the timer, then up to TEXT_ROWS lines of text to type.
+    let text_rows = TEXT_ROWS.min(screen_sections[1].height.saturating_sub(1));
     let centered_body = center(
         screen_sections[1],
         Length(screen_frame.area().width),
-        Length(7), // 1 timer row, 6 lines of text
+        Length(1 + text_rows),
     );
 
     // Split the centered rows into space for the timer and space for the challenge words.
     let [timer_section, words_section] =
-        Layout::vertical([Length(1), Min(5)]).areas::<2>(centered_body);
+        Layout::vertical([Length(1), Min(1)]).areas::<2>(centered_body);
 
     // Horizontal padding for the centered content (timer + challenge words).
-    let h_pad = 8;
+    let h_pad = if compact { 1 } else { 8 };
 
-    // The game timer - shows as dim until the game starts.
-    let game_time_remaining_secs = app.game_time_remaining_millis().div_ceil(1000);
+    // The game timer - shows as dim until the game starts. Zen mode has no time limit,
+    // so the timer counts up instead.
+    let game_time_secs = if app.game.is_zen_mode() {
+        app.game.elapsed().as_secs()
+    } else {
+        (app.game.remaining().as_millis() as u64).div_ceil(1000)
+    };
     let mut timer_style = Style::default()
         .fg(current_theme.primary)
         .add_modifier(Modifier::DIM);
 
-    if app.game_active {
+    if app.game.is_active() {
         timer_style = timer_style
             .add_modifier(Modifier::BOLD)
             .remove_modifier(Modifier::DIM);
     }
 
     // When the game is almost over, we underline the timer.
-    if game_time_remaining_secs <= 3 {
+    if !app.game.is_zen_mode() && game_time_secs <= 3 {
         timer_style = timer_style.add_modifier(Modifier::UNDERLINED);
     }
 
     let game_timer = Paragraph::new(Text::styled(
-        game_time_remaining_secs.to_string(),
+        game_time_secs.to_string(),
         timer_style,
     ))
     .bg(current_theme.bg)
@@ -271,7 +297,7 @@ fn build_game_screen(screen_frame: &mut Frame, app: &mut App) {
     });
 
     let text_render_area_width = screen_sections[1].inner(Margin::new(h_pad, 0)).width;
-    let mut wrapper = WordWrapper::new(styled.clone().into_iter(), text_render_area_width, false);
+    let mut wrapper = WordWrapper::new(styled.clone(), text_render_area_width, false);
 
     // Continuously sum the widths until we get to the cursor offset.
     // At that point we know we're at the cursor char, and can check the line number
@@ -281,36 +307,41 @@ fn build_game_screen(screen_frame: &mut Frame, app: &mut App) {
     let mut cursor_found = false;
     let mut wrapped_lines = vec![];
     let mut line_alpha = 1.0;
+    // In memory mode, characters which haven't been typed yet are masked once the
+    // current line has been on screen for long enough.
+    let hide_upcoming = app.upcoming_words_hidden();
     while let Some(wrapped_line) = wrapper.next_line() {
-        let line_symbols = wrapped_line
-            .line
-            .iter()
-            .map(|grapheme| {
-                Span::styled(
-                    grapheme.symbol,
-                    grapheme
-                        .style
-                        .patch(grapheme.style.fg.map_or(current_theme.fg, |fg| {
-                            if current_theme.supports_alpha {
-                                blend_colors(fg, current_theme.bg, line_alpha)
-                            } else {
-                                fg
-                            }
-                        })),
-                )
-            })
-            .collect::<Line>();
-
-        wrapped_lines.push(line_symbols);
+        let mut line_symbols = Line::default();
         for grapheme in wrapped_line.line {
+            let mut is_upcoming = false;
             if grapheme.symbol != " " {
                 offset_from_start_of_text += grapheme.symbol.width();
-                if offset_from_start_of_text > cursor_offset && !cursor_found {
-                    cursor_row = row;
-                    cursor_found = true;
+                if offset_from_start_of_text > cursor_offset {
+                    is_upcoming = true;
+                    if !cursor_found {
+                        cursor_row = row;
+                        cursor_found = true;
+                    }
                 }
             }
+
+            let style = grapheme
+                .style
+                .patch(grapheme.style.fg.map_or(current_theme.fg, |fg| {
+                    if current_theme.supports_alpha {
+                        blend_colors(fg, current_theme.bg, line_alpha)
+                    } else {
+                        fg
+                    }
+                }));
+            if is_upcoming && hide_upcoming && cursor_row == app.memory_row {
+                let mask = MEMORY_MASK_SYMBOL.repeat(grapheme.symbol.width());
+                line_symbols.push_span(Span::styled(mask, style));
+            } else {
+                line_symbols.push_span(Span::styled(grapheme.symbol, style));
+            }
         }
+        wrapped_lines.push(line_symbols);
 
         // Start dimming towards the bottom
         if cursor_found && row > cursor_row && row > 2 {
@@ -318,14 +349,15 @@ fn build_game_screen(screen_frame: &mut Frame, app: &mut App) {
         }
         row += 1;
     }
+    app.set_memory_row(cursor_row);
 
     let mut words_paragraph = Paragraph::new(Text::from(wrapped_lines))
         .wrap(Wrap { trim: false })
         .block(Block::default().padding(Padding::horizontal(h_pad)));
 
-    if cursor_row > 2 {
-        words_paragraph = words_paragraph.scroll((cursor_row - 2, 0));
-    }
+    // Keep a couple of typed lines visible above the cursor, if there's room for them.
+    let rows_above_cursor = (words_section.height.saturating_sub(1) / 2).min(2);
+    words_paragraph = words_paragraph.scroll((cursor_row.saturating_sub(rows_above_cursor), 0));
 
     screen_frame.render_widget(words_paragraph, words_section);
 
@@ -356,27 +388,23 @@ fn build_header(app: &App) -> Paragraph<'static> {
             .add_modifier(Modifier::DIM)
             .remove_modifier(Modifier::BOLD),
     );
-    let title = Paragraph::new(title_text).block(header_block);
-    title
+    if let Some(book) = &app.book {
+        title_text += Span::styled(
+            format!("  {}", book.location()),
+            Style::default().fg(current_theme.fg),
+        );
+    }
+    Paragraph::new(title_text).block(header_block)
 }
 
 fn build_score_screen(screen_frame: &mut Frame, app: &mut App) {
     let current_theme = app.get_current_theme();
-    let [header_rect, body_rect, footer_rect] = Layout::default()
-        .horizontal_margin(3)
-        .vertical_margin(1)
-        .direction(Direction::Vertical)
-        .constraints([
-            Length(1), // Header
-            Min(2),    // Body
-            Length(1), // Footer
-        ])
-        .areas(screen_frame.area());
+    let [header_rect, body_rect, footer_rect] = screen_sections(screen_frame.area());
 
     screen_frame.render_widget(build_header(app), header_rect);
 
     // Score screen body
-    let score = &app.score;
+    let score = app.game.score();
     let score_data = vec![
         ResultData {
             theme: current_theme.clone(),
@@ -411,8 +439,19 @@ fn build_score_screen(screen_frame: &mut Frame, app: &mut App) {
     ];
     let col_constraints = (0..3).map(|_| Length(10));
     let mut row_constraints = (0..2).map(|_| Length(3)).collect::<Vec<_>>();
-    let is_perfect_score = app.score.is_perfect();
-    if is_perfect_score {
+    // A single line shown above the results table, for perfect or failed games.
+    let banner = match app.current_screen {
+        Screen::Failed(reason) => Some(Line::styled(
+            format!("Failed: {}", reason.description()),
+            Style::default().fg(current_theme.error).italic(),
+        )),
+        _ if app.game.score().is_perfect() => Some(Line::styled(
+            "Perfect!",
+            Style::default().fg(current_theme.secondary).italic(),
+        )),
+        _ => None,
+    };
+    if banner.is_some() {
         row_constraints.insert(0, Length(1));
     }
 
@@ -423,24 +462,18 @@ fn build_score_screen(screen_frame: &mut Frame, app: &mut App) {
         .horizontal_margin(1);
 
     let rows = vertical.split(body_rect);
-    // If the score is perfect, then we've added an extra constraint to insert "PERFECT" text,
+    // If there's a banner, then we've added an extra constraint to insert its text,
     // so skip that as it's not one of the "table cells" we'll insert our data into.
-    let num_skips = if is_perfect_score { 1 } else { 0 };
+    let num_skips = if banner.is_some() { 1 } else { 0 };
     let cells = rows
         .iter()
         .skip(num_skips)
         .flat_map(|&row| horizontal.split(row).to_vec())
         .collect::<Vec<_>>();
 
-    if is_perfect_score {
-        let perfect_score_section = rows.iter().next().unwrap();
-        screen_frame.render_widget(
-            Line::styled(
-                "Perfect!",
-                Style::default().fg(current_theme.secondary).italic(),
-            ),
-            *perfect_score_section,
-        );
+    if let Some(banner) = banner {
+        let banner_section = rows.iter().next().unwrap();
+        screen_frame.render_widget(banner, *banner_section);
     }
     for (score_data, cell_area) in score_data.into_iter().zip(cells) {
         screen_frame.render_widget(score_data, cell_area);
@@ -461,8 +494,37 @@ fn build_footer(
     show_reset: bool,
 ) {
     let current_theme = app.get_current_theme();
-    let score_constraint = if show_scoring { Min(10) } else { Max(0) };
-    let footer_sections: [Rect; 2] = Layout::horizontal([Constraint::Fill(1), score_constraint])
+
+    let score_text = if show_scoring {
+        let empty_score_placeholder = "-";
+        let score = app.game.score();
+        let accuracy = if app.game.is_active() && !score.accuracy.is_nan() {
+            format!("{:.0}%", score.accuracy * 100.0)
+        } else {
+            empty_score_placeholder.to_string()
+        };
+        let wpm = if app.game.is_active() && !score.wpm.is_nan() && score.wpm != 0.0 {
+            format!("{:.0}", score.wpm)
+        } else {
+            empty_score_placeholder.to_string()
+        };
+
+        let mut score_text = Line::default();
+        // Accuracy would give away typos, so it's hidden in blind mode.
+        if !app.config.blind {
+            score_text.push_span(Span::raw("acc "));
+            score_text.push_span(Span::raw(accuracy).fg(current_theme.fg).dim());
+            score_text.push_span(Span::raw("  ").fg(current_theme.fg).dim());
+        }
+        score_text.push_span(Span::raw("wpm "));
+        score_text.push_span(Span::raw(wpm).fg(current_theme.fg).dim());
+        Some(score_text)
+    } else {
+        None
+    };
+    // The score gets as much room as it needs (plus padding), and the keys get the rest.
+    let score_width = score_text.as_ref().map_or(0, |text| text.width() as u16 + 1);
+    let footer_sections: [Rect; 2] = Layout::horizontal([Constraint::Fill(1), Length(score_width)])
         .flex(SpaceBetween)
         .areas(rect);
 
@@ -475,55 +537,145 @@ fn build_footer(
     let value_style = Style::default()
         .fg(current_theme.fg)
         .add_modifier(Modifier::DIM);
-    let mut keys = Line::from(vec![
-        Span::styled("ESC ", key_style),
-        Span::styled("quit  ", value_style),
-    ]);
+    let in_settings = matches!(app.current_screen, Screen::Settings);
+    let in_menu = in_settings || matches!(app.current_screen, Screen::Stats(_));
+    let bindings = &app.config.keybindings;
+    // Menus are always closed with escape, whatever quit is bound to.
+    let mut keys = if in_menu {
+        Line::from(vec![
+            Span::styled("ESC ", key_style),
+            Span::styled("back  ", value_style),
+        ])
+    } else {
+        Line::from(vec![
+            Span::styled(format!("{} ", bindings.label(Action::Quit)), key_style),
+            Span::styled("quit  ", value_style),
+        ])
+    };
+    let quit_keys = keys.clone();
+    if in_settings {
+        keys.push_span(Span::styled("←→ ", key_style));
+        keys.push_span(Span::styled("change ", value_style));
+    }
     if show_reset {
-        keys.push_span(Span::styled("TAB ", key_style));
+        keys.push_span(Span::styled(format!("{} ", bindings.label(Action::Restart)), key_style));
         keys.push_span(Span::styled("restart ", value_style));
     }
-    let keys_paragraph = Paragraph::new(keys).block(keys_block);
-
+    if app.game.is_zen_mode() && matches!(app.current_screen, Screen::Game) {
+        keys.push_span(Span::styled(format!(" {} ", bindings.label(Action::Finish)), key_style));
+        keys.push_span(Span::styled("finish ", value_style));
+    }
+    // On narrow terminals, only the way out is shown.
     let footer_left_corner = footer_sections[0];
+    if keys.width() as u16 + 1 > footer_left_corner.width {
+        keys = quit_keys;
+    }
+    // Errors take the place of the keys until the next key press.
+    if let Some(error) = &app.error {
+        keys = Line::styled(format!("error: {}", error), Style::default().fg(current_theme.error));
+    }
+    let keys_paragraph = Paragraph::new(keys).block(keys_block);
     screen_frame.render_widget(keys_paragraph, footer_left_corner);
 
-    let footer_right_corner = footer_sections[1];
-    if show_scoring {
-        let empty_score_placeholder = "-";
-        let score = &app.score;
+    if let Some(score_text) = score_text {
         let score_block = Block::default()
             .padding(Padding::right(1))
             .fg(current_theme.primary)
             .bg(current_theme.bg);
-
-        let accuracy = if app.game_active && !score.accuracy.is_nan() {
-            format!("{:.0}%", score.accuracy * 100.0)
-        } else {
-            empty_score_placeholder.to_string()
-        };
-        let wpm = if app.game_active && !score.wpm.is_nan() && score.wpm != 0.0 {
-            format!("{:.0}", score.wpm)
-        } else {
-            empty_score_placeholder.to_string()
-        };
-
-        let score_text = Line::from(vec![
-            Span::raw("acc "),
-            Span::raw(accuracy).fg(current_theme.fg).dim(),
-            Span::raw("  ").fg(current_theme.fg).dim(),
-            Span::raw("wpm "),
-            Span::raw(wpm).fg(current_theme.fg).dim(),
-        ]);
-        let score_text = Text::from(score_text);
-        let score_paragraph = Paragraph::new(score_text)
+        let score_paragraph = Paragraph::new(Text::from(score_text))
             .alignment(Alignment::Right)
             .block(score_block);
+        screen_frame.render_widget(score_paragraph, footer_sections[1]);
+    }
+}
+
+fn build_settings_screen(screen_frame: &mut Frame, app: &mut App) {
+    let current_theme = app.get_current_theme();
+    let [header_rect, body_rect, footer_rect] = screen_sections(screen_frame.area());
 
-        screen_frame.render_widget(score_paragraph, footer_right_corner);
+    screen_frame.render_widget(build_header(app), header_rect);
+
+    let settings = app.settings();
+    let key_width = settings.iter().map(|setting| setting.key.len()).max().unwrap_or(0);
+    // The list, with a line about the keys used here below it.
+    let area = center(body_rect, Length(60), Length(settings.len() as u16 + 2));
+    let [list_area, _, help_area] =
+        Layout::vertical([Min(1), Length(1), Length(1)]).areas(area);
+
+    // Scroll the list so that the selected option is always visible.
+    let visible_settings = list_area.height as usize;
+    let first_visible = (app.settings_selected + 1).saturating_sub(visible_settings);
+    let lines = settings
+        .iter()
+        .enumerate()
+        .skip(first_visible)
+        .take(visible_settings)
+        .map(|(index, setting)| {
+            let value = setting.value_label(&app.config);
+            let source = app.config_source(setting.key).label();
+            let (marker, style) = if index == app.settings_selected {
+                ("▸ ", Style::default().fg(current_theme.secondary).bold())
+            } else {
+                ("  ", Style::default().fg(current_theme.fg))
+            };
+            Line::from(vec![
+                Span::styled(format!("{}{:<key_width$}  ", marker, setting.key), style),
+                Span::styled(format!("{:<20} ", value), style.not_dim()),
+                Span::styled(source, Style::default().fg(current_theme.fg).dim()),
+            ])
+        })
+        .collect::<Vec<_>>();
+    screen_frame.render_widget(Text::from(lines), list_area);
+    // Only the actions in [keybindings] can be rebound, not the keys for getting around menus.
+    let help = Line::styled(
+        "menu keys (esc, enter, tab, arrows, hjkl) can't be rebound",
+        Style::default().fg(current_theme.fg).dim(),
+    );
+    screen_frame.render_widget(help, help_area);
+
+    build_footer(screen_frame, footer_rect, app, false, false);
+}
+
+fn build_stats_screen(screen_frame: &mut Frame, app: &mut App) {
+    let Screen::Stats(stats) = &app.current_screen else {
+        return;
+    };
+    let current_theme = app.get_current_theme();
+    let [header_rect, body_rect, footer_rect] = screen_sections(screen_frame.area());
+
+    screen_frame.render_widget(build_header(app), header_rect);
+
+    let stats_data = [
+        (stats.games.to_string(), "games"),
+        (stats.completed.to_string(), "completed"),
+        (format!("{:.0}", stats.best_wpm), "best wpm"),
+        (format!("{:.0}", stats.average_wpm), "average wpm"),
+        (format!("{:.0}", stats.recent_wpm), "last 10 wpm"),
+        (format!("{:.0}%", stats.average_accuracy * 100.), "accuracy"),
+    ]
+    .map(|(value, subtext)| ResultData {
+        theme: current_theme.clone(),
+        value,
+        subtext: subtext.to_string(),
+    });
+    let horizontal = Layout::horizontal((0..3).map(|_| Length(12))).spacing(1);
+    let vertical = Layout::vertical((0..2).map(|_| Length(3)))
+        .flex(Center)
+        .spacing(1)
+        .horizontal_margin(1);
+    let cells = vertical
+        .split(body_rect)
+        .iter()
+        .flat_map(|&row| horizontal.split(row).to_vec())
+        .collect::<Vec<_>>();
+    for (data, cell_area) in stats_data.into_iter().zip(cells) {
+        screen_frame.render_widget(data, cell_area);
     }
+
+    build_footer(screen_frame, footer_rect, app, false, false);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_styled_word(
     app: &App,
     words_text: &mut Text,
@@ -535,7 +687,6 @@ fn build_styled_word(
     ghost_cursor_offset: Option<usize>,
 ) {
     let current_theme = app.get_current_theme();
-    let mut offset_in_word = 0;
     let zipped_chars = expected_word
         .chars()
         .zip(user_attempt.chars())
@@ -558,30 +709,48 @@ fn build_styled_word(
         }
     }
 
-    for (expected_char, user_char) in zipped_chars {
+    // In blind mode every typed character gets the same styling, so that the user
+    // can't tell whether they typed it correctly until the results screen.
+    let blind = app.config.blind;
+    let blind_char_style = char_style.patch(current_theme.character_blind());
+
+    for (offset_in_word, (expected_char, user_char)) in zipped_chars.into_iter().enumerate() {
         let mut style = char_style;
         let mut span;
-        if user_char == expected_char {
+        if blind {
+            span = Span::styled(expected_char.to_string(), blind_char_style);
+        } else if user_char == expected_char {
             style = style.patch(current_theme.character_match);
             span = Span::styled(expected_char.to_string(), style);
         } else {
-            span = Span::styled(
-                expected_char.to_string(),
-                char_style.patch(current_theme.character_mismatch),
-            );
+            let mismatch_style = char_style.patch(current_theme.character_mismatch);
+            span = match app.config.error_indicator {
+                ErrorIndicator::None => Span::styled(expected_char.to_string(), mismatch_style),
+                ErrorIndicator::Underline => Span::styled(
+                    expected_char.to_string(),
+                    mismatch_style.add_modifier(Modifier::UNDERLINED),
+                ),
+                ErrorIndicator::Reverse => Span::styled(
+                    expected_char.to_string(),
+                    mismatch_style.add_modifier(Modifier::REVERSED),
+                ),
+                ErrorIndicator::Glyph => {
+                    // Typed whitespace would be invisible, so show a placeholder instead.
+                    let glyph = if user_char.is_whitespace() {
+                        '·'
+                    } else {
+                        user_char
+                    };
+                    Span::styled(glyph.to_string(), mismatch_style)
+                }
+            };
         }
 
-        match ghost_cursor_offset {
-            Some(ghost_cursor_offset) => {
-                if ghost_cursor_offset == offset_in_word {
-                    span = span.bg(current_theme.ghost_cursor_color());
-                }
-            }
-            None => {}
+        if ghost_cursor_offset == Some(offset_in_word) {
+            span = span.bg(current_theme.ghost_cursor_color());
         }
 
         words_text.push_span(span);
-        offset_in_word += 1;
     }
 
     let current_theme = app.get_current_theme();
@@ -590,7 +759,7 @@ fn build_styled_word(
     // (not that they typed incorrectly - this is for when you press space too
     // early, before getting to the end of a word).
     let mut missed_char_style = char_style;
-    if is_past_word {
+    if is_past_word && !blind {
         missed_char_style = missed_char_style
             .patch(current_theme.character_mismatch)
             .add_modifier(Modifier::UNDERLINED);
@@ -617,34 +786,171 @@ fn build_styled_word(
 
     for (idx, missed_char) in missed_chars_iter.enumerate() {
         let mut char_style = missed_char_style;
-        if let Some(ghost_cursor_offset) = ghost_cursor_offset {
-            if ghost_cursor_offset == min_len + idx + 1 {
-                char_style = char_style.bg(current_theme.ghost_cursor_color());
-            }
+        if ghost_cursor_offset == Some(min_len + idx + 1) {
+            char_style = char_style.bg(current_theme.ghost_cursor_color());
         }
         words_text.push_span(Span::styled(missed_char.to_string(), char_style));
     }
 
     // Render extra chars that the user typed beyond the length of the word
     let extra_chars_iter = user_attempt.chars().skip(min_len);
-    let extra_chars_span = Span::styled(
-        extra_chars_iter.collect::<String>(),
+    let extra_chars_style = if blind {
+        blind_char_style
+    } else {
         char_style
             .patch(current_theme.character_mismatch)
-            .add_modifier(Modifier::CROSSED_OUT),
-    );
+            .add_modifier(Modifier::CROSSED_OUT)
+    };
+    let extra_chars_span = Span::styled(extra_chars_iter.collect::<String>(), extra_chars_style);
     words_text.push_span(extra_chars_span);
 }
 
+fn build_theme_picker(screen_frame: &mut Frame, app: &App) {
+    let Some(picker) = &app.theme_picker else {
+        return;
+    };
+    let matches = app.theme_picker_matches();
+    build_picker(
+        screen_frame,
+        app,
+        " themes ",
+        &picker.filter,
+        &matches,
+        picker.selected,
+        "no matching themes",
+    );
+}
+
+fn build_command_palette(screen_frame: &mut Frame, app: &App) {
+    let Some(palette) = &app.command_palette else {
+        return;
+    };
+    let labels = app
+        .command_palette_matches()
+        .iter()
+        .map(|action| action.label())
+        .collect::<Vec<_>>();
+    build_picker(
+        screen_frame,
+        app,
+        " commands ",
+        &palette.filter,
+        &labels,
+        palette.selected,
+        "no matching commands",
+    );
+}
+
+// A popup with a filter line above a scrolling list of items, one of which is selected.
+fn build_picker(
+    screen_frame: &mut Frame,
+    app: &App,
+    title: &str,
+    filter: &str,
+    items: &[String],
+    selected: usize,
+    no_matches_message: &str,
+) {
+    let current_theme = app.get_current_theme();
+
+    // 1 row for the filter, the list of items, and 2 rows for the border.
+    let max_visible_items = 8;
+    let area = center(
+        screen_frame.area(),
+        Length(36),
+        Length(max_visible_items + 3),
+    );
+    let block = Block::bordered()
+        .title(title)
+        .border_style(Style::default().fg(current_theme.primary))
+        .fg(current_theme.fg)
+        .bg(current_theme.bg)
+        .padding(Padding::horizontal(1));
+    let inner_area = block.inner(area);
+    Clear.render(area, screen_frame.buffer_mut());
+    screen_frame.render_widget(block, area);
+
+    let [filter_area, list_area] = Layout::vertical([Length(1), Min(0)]).areas(inner_area);
+    let filter = Line::from(vec![
+        Span::styled("> ", Style::default().fg(current_theme.primary)),
+        Span::raw(filter),
+        Span::styled(" ", cursor_type_to_ratatui_style(&CursorType::Block, app)),
+    ]);
+    screen_frame.render_widget(filter, filter_area);
+
+    if items.is_empty() {
+        let no_matches = Line::styled(no_matches_message, Style::default().dim());
+        screen_frame.render_widget(no_matches, list_area);
+        return;
+    }
+
+    // Scroll the list so that the selected item is always visible.
+    let visible_items = list_area.height as usize;
+    let first_visible = (selected + 1).saturating_sub(visible_items);
+    let lines = items
+        .iter()
+        .enumerate()
+        .skip(first_visible)
+        .take(visible_items)
+        .map(|(index, item)| {
+            if index == selected {
+                Line::styled(
+                    format!("▸ {}", item),
+                    Style::default().fg(current_theme.secondary).bold(),
+                )
+            } else {
+                Line::styled(format!("  {}", item), Style::default().dim())
+            }
+        })
+        .collect::<Vec<_>>();
+    screen_frame.render_widget(Text::from(lines), list_area);
+}
+
 fn center(area: Rect, horizontal: Constraint, vertical: Constraint) -> Rect {
     let [area] = Layout::horizontal([horizontal]).flex(Center).areas(area);
     let [area] = Layout::vertical([vertical])
         .flex(Center)
         .areas(area)
-        .map(|rect| rect.offset(Offset { x: 0, y: -1 }));
+        // Sit slightly above the middle, if there's room to.
+        .map(|rect| if rect.y > area.y { rect.offset(Offset { x: 0, y: -1 }) } else { rect });
     area
 }
 
+fn is_compact(area: Rect) -> bool {
+    area.width < COMPACT_WIDTH || area.height < COMPACT_HEIGHT
+}
+
+// Splits the screen into the header, body and footer rows.
+fn screen_sections(area: Rect) -> [Rect; 3] {
+    let (horizontal_margin, vertical_margin) = if is_compact(area) { (1, 0) } else { (3, 1) };
+    Layout::default()
+        .horizontal_margin(horizontal_margin)
+        .vertical_margin(vertical_margin)
+        .direction(Direction::Vertical)
+        .constraints([
+            Length(1), // Header
+            Min(0),    // Body
+            Length(1), // Footer
+        ])
+        .areas(area)
+}
+
+fn build_too_small_screen(screen_frame: &mut Frame, app: &App) {
+    let current_theme = app.get_current_theme();
+    let area = screen_frame.area();
+    let message = Text::from(vec![
+        Line::styled("Terminal too small", Style::default().fg(current_theme.primary).bold()),
+        Line::styled(
+            format!("{}x{}, need {}x{}", area.width, area.height, MIN_WIDTH, MIN_HEIGHT),
+            Style::default().fg(current_theme.fg).dim(),
+        ),
+    ]);
+    // Leave room for each line to wrap once on very narrow terminals.
+    let message_area = center(area, Length(area.width), Length(4));
+    let message = Paragraph::new(message).centered().wrap(Wrap { trim: true });
+    screen_frame.render_widget(message, message_area);
+}
+
 fn cursor_type_to_ratatui_style(cursor_style: &CursorType, app: &App) -> Style {
     let current_theme = app.get_current_theme();
     match cursor_style {
@@ -0,0 +1,225 @@
+//! Snapshot tests for the UI. Each test drives an `App` with scripted key presses and a manual
+//! clock, renders it into a `TestBackend`, and compares the buffer (text and styles) with a
+//! snapshot file in `tests/snapshots`.
+//!
+//! Run `UPDATE_SNAPSHOTS=1 cargo test` to write new snapshots after an intentional UI change,
+//! then review the diff before committing.
+
+use crate::app::{get_themes, new_game, App};
+use crate::config::Config;
+use crate::history::History;
+use crate::terminal::ColorDepth;
+use crate::ui::ui;
+use crate::{end_game_if_finished, handle_key};
+use o4t::clock::ManualClock;
+use o4t::game::Game;
+use o4t::generator::WordGenerator;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::Terminal;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+use std::{env, fs, process};
+
+// Endlessly repeats a pangram, so the words on screen are the same every time.
+struct Pangram(usize);
+
+const PANGRAM: [&str; 9] = ["the", "quick", "brown", "fox", "jumps", "over", "the", "lazy", "dog"];
+
+impl WordGenerator for Pangram {
+    fn next_word(&mut self) -> Option<String> {
+        let word = PANGRAM[self.0 % PANGRAM.len()];
+        self.0 += 1;
+        Some(word.to_string())
+    }
+}
+
+struct Harness {
+    app: App,
+    clock: ManualClock,
+    terminal: Terminal<TestBackend>,
+    history: History,
+    dir: PathBuf,
+}
+
+impl Harness {
+    fn new(name: &str, width: u16, height: u16, config: Config) -> Harness {
+        let dir = env::temp_dir().join(format!("o4t-ui-tests-{}-{}", process::id(), name));
+        fs::create_dir_all(&dir).unwrap();
+        // Colours would otherwise depend on the terminal running the tests.
+        let config = Config {
+            colors: ColorDepth::Truecolor,
+            ..config
+        };
+        let clock = ManualClock::new();
+        let mut app = App::with_config(Rc::new(config), get_themes(), Rc::new(clock.clone()));
+        app.game = Game::new(
+            app.game.config().clone(),
+            Box::new(Pangram(0)),
+            Rc::new(clock.clone()),
+        );
+        Harness {
+            app,
+            clock,
+            terminal: Terminal::new(TestBackend::new(width, height)).unwrap(),
+            history: History::new(dir.join("history.jsonl")),
+            dir,
+        }
+    }
+
+    fn config_file(&self) -> PathBuf {
+        self.dir.join("config.toml")
+    }
+
+    fn press(&mut self, code: KeyCode) {
+        let key = KeyEvent::new(code, KeyModifiers::NONE);
+        let config_file = self.config_file();
+        handle_key(&mut self.app, key, &self.history, &config_file).unwrap();
+    }
+
+    fn type_text(&mut self, text: &str) {
+        for char in text.chars() {
+            self.press(KeyCode::Char(char));
+        }
+    }
+
+    /// Moves time forward, ticking the game like the main loop does.
+    fn advance(&mut self, duration: Duration) {
+        self.clock.advance(duration);
+        self.app.game.tick();
+        end_game_if_finished(&mut self.app, &self.history);
+    }
+
+    /// Renders the app, returning the buffer (with its styles) as text.
+    fn render(&mut self) -> String {
+        // Long enough for the load effects to finish, so only the final frame is captured.
+        self.app.last_tick_duration = Duration::from_secs(1);
+        self.terminal.draw(|frame| ui(frame, &mut self.app)).unwrap();
+        format!("{:#?}", self.terminal.backend().buffer())
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{}.snap", name));
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let Ok(expected) = fs::read_to_string(&path) else {
+        panic!(
+            "snapshot {} is missing, rerun with UPDATE_SNAPSHOTS=1 to write it",
+            path.display()
+        );
+    };
+    if expected != actual {
+        panic!(
+            "snapshot {} doesn't match, rerun with UPDATE_SNAPSHOTS=1 to update it\n\
+             --- expected\n{}\n--- actual\n{}",
+            path.display(),
+            expected,
+            actual
+        );
+    }
+}
+
+#[test]
+fn game_screen_before_typing() {
+    let mut harness = Harness::new("waiting", 60, 14, Config::default());
+    assert_snapshot("game_waiting", &harness.render());
+}
+
+#[test]
+fn game_screen_with_mistakes_and_ghost() {
+    let config = Config {
+        target_wpm: 60,
+        ..Config::default()
+    };
+    let mut harness = Harness::new("ghost", 60, 14, config);
+    harness.type_text("the quikc brwn fo");
+    // At 60 wpm the ghost has typed 15 characters after 3 seconds.
+    harness.advance(Duration::from_secs(3));
+    assert_snapshot("game_mistakes_ghost", &harness.render());
+}
+
+#[test]
+fn ghost_cursor_in_accented_words() {
+    let config = Config {
+        target_wpm: 60,
+        ..Config::default()
+    };
+    let mut harness = Harness::new("ghost-accents", 60, 14, config);
+    let clock = Rc::new(harness.clock.clone());
+    harness.app.game = new_game(&harness.app.config, Some("naïve café crème"), clock);
+    harness.type_text("na");
+    // 11 characters in, the ghost is on the space after "café", whose "é" takes two bytes.
+    harness.advance(Duration::from_millis(2200));
+    assert_snapshot("game_ghost_accents", &harness.render());
+}
+
+#[test]
+fn game_screen_wraps_and_scrolls() {
+    let mut harness = Harness::new("scroll", 40, 14, Config::default());
+    harness.type_text("the quick brown fox jumps over the lazy dog ");
+    harness.type_text("the quick brown fox jumps over the lazy dog ");
+    harness.type_text("the quick brown fox jumps over the lazy dog the");
+    harness.press(KeyCode::Backspace);
+    harness.advance(Duration::from_secs(10));
+    assert_snapshot("game_wrap_scroll", &harness.render());
+}
+
+#[test]
+fn results_screen() {
+    let config = Config {
+        time: 15,
+        ..Config::default()
+    };
+    let mut harness = Harness::new("results", 60, 14, config);
+    harness.type_text("the quick brwn fox jumsp over ");
+    harness.press(KeyCode::Backspace);
+    harness.advance(Duration::from_secs(16));
+    assert_snapshot("results", &harness.render());
+}
+
+#[test]
+fn results_screen_with_perfect_score() {
+    let config = Config {
+        time: 15,
+        ..Config::default()
+    };
+    let mut harness = Harness::new("perfect", 60, 14, config);
+    harness.type_text("the quick brown fox ");
+    harness.advance(Duration::from_secs(15));
+    assert_snapshot("results_perfect", &harness.render());
+}
+
+#[test]
+fn game_screen_in_a_short_terminal() {
+    let mut harness = Harness::new("short", 40, 7, Config::default());
+    harness.type_text("the quick brown fox jumps over the lazy dog the quick bro");
+    assert_snapshot("game_short", &harness.render());
+}
+
+#[test]
+fn terminal_too_small() {
+    let mut harness = Harness::new("too-small", 16, 5, Config::default());
+    assert_snapshot("too_small", &harness.render());
+}
+
+#[test]
+fn game_screen_after_resize() {
+    let mut harness = Harness::new("resize", 60, 14, Config::default());
+    harness.type_text("the quick brown fox jumps ");
+    harness.render();
+    harness.terminal.backend_mut().resize(30, 10);
+    assert_snapshot("game_resized", &harness.render());
+}
@@ -128,9 +128,11 @@ where
 
             // pending line fills up limit
             let line_full = line_width >= self.max_line_width;
-            // pending word would overflow line limit
+            // pending word would overflow line limit. The symbol's own width is included, so
+            // wide symbols can't push the last line of the input past the limit.
             let pending_word_overflow = symbol_width > 0
-                && line_width + whitespace_width + word_width >= self.max_line_width;
+                && line_width + whitespace_width + word_width + symbol_width
+                    > self.max_line_width;
 
             // add finished wrapped line to remaining lines
             if line_full || pending_word_overflow {
@@ -152,6 +154,23 @@ where
                     self.pending_whitespace.pop_front();
                 }
 
+                // pending word still wouldn't fit alongside a wide symbol, so break it here
+                if !self.pending_word.is_empty()
+                    && whitespace_width + word_width + symbol_width > self.max_line_width
+                {
+                    let mut word_line = self.pending_line_pool.pop().unwrap_or_default();
+                    word_line.clear();
+                    if !self.trim {
+                        word_line.extend(self.pending_whitespace.drain(..));
+                    }
+                    word_line.append(&mut self.pending_word);
+                    self.wrapped_lines.push_back(word_line);
+
+                    self.pending_whitespace.clear();
+                    whitespace_width = 0;
+                    word_width = 0;
+                }
+
                 // don't count first whitespace toward next word
                 if is_whitespace && self.pending_whitespace.is_empty() {
                     continue;
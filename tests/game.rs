@@ -0,0 +1,95 @@
+use o4t::clock::ManualClock;
+use o4t::game::{Game, GameConfig, GameState, InputEvent, Mode};
+use o4t::generator::WordGenerator;
+use std::rc::Rc;
+use std::time::Duration;
+
+// Endlessly repeats the same words, so games are predictable.
+struct RepeatedWords(Vec<&'static str>, usize);
+
+impl WordGenerator for RepeatedWords {
+    fn next_word(&mut self) -> Option<String> {
+        let word = self.0[self.1 % self.0.len()];
+        self.1 += 1;
+        Some(word.to_string())
+    }
+}
+
+fn new_game(mode: Mode, seconds: u64) -> (Game, ManualClock) {
+    let clock = ManualClock::new();
+    let config = GameConfig {
+        mode,
+        time: Duration::from_secs(seconds),
+        sudden_death: false,
+        min_accuracy: 0,
+    };
+    let words = Box::new(RepeatedWords(vec!["the", "cat"], 0));
+    (Game::new(config, words, Rc::new(clock.clone())), clock)
+}
+
+fn type_text(game: &mut Game, text: &str) {
+    for char in text.chars() {
+        game.handle_input(InputEvent::Char(char));
+    }
+}
+
+#[test]
+fn game_starts_on_first_keypress() {
+    let (mut game, clock) = new_game(Mode::Time, 30);
+    clock.advance(Duration::from_secs(10));
+    game.tick();
+    assert_eq!(game.state(), GameState::Waiting);
+    assert_eq!(game.remaining(), Duration::from_secs(30));
+
+    type_text(&mut game, "t");
+    clock.advance(Duration::from_secs(5));
+    game.tick();
+    assert_eq!(game.state(), GameState::Active);
+    assert_eq!(game.started_at(), Some(Duration::from_secs(10)));
+    assert_eq!(game.elapsed(), Duration::from_secs(5));
+}
+
+#[test]
+fn timed_game_lasts_exactly_its_duration() {
+    let (mut game, clock) = new_game(Mode::Time, 30);
+    type_text(&mut game, "the ");
+
+    clock.advance(Duration::from_millis(29_999));
+    game.tick();
+    assert!(game.is_active());
+
+    // However late the game notices, it ends when the time ran out.
+    clock.advance(Duration::from_millis(5_001));
+    game.tick();
+    assert_eq!(game.state(), GameState::Finished(None));
+    assert_eq!(game.elapsed(), Duration::from_secs(30));
+
+    // 3 matching characters and 1 correct word (for the space) in half a minute.
+    assert_eq!(game.score().wpm, 1.6);
+}
+
+#[test]
+fn input_after_time_is_up_is_ignored() {
+    let (mut game, clock) = new_game(Mode::Time, 15);
+    type_text(&mut game, "th");
+    clock.advance(Duration::from_secs(20));
+
+    type_text(&mut game, "e");
+    assert_eq!(game.state(), GameState::Finished(None));
+    assert_eq!(game.current_user_input(), "th");
+    assert_eq!(game.elapsed(), Duration::from_secs(15));
+}
+
+#[test]
+fn zen_game_lasts_until_finished() {
+    let (mut game, clock) = new_game(Mode::Zen, 30);
+    type_text(&mut game, "hello");
+    clock.advance(Duration::from_secs(60));
+    game.tick();
+    assert!(game.is_active());
+
+    game.handle_input(InputEvent::Finish);
+    clock.advance(Duration::from_secs(10));
+    assert_eq!(game.state(), GameState::Finished(None));
+    assert_eq!(game.elapsed(), Duration::from_secs(60));
+}
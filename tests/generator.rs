@@ -0,0 +1,77 @@
+use o4t::generator::{NoWords, Punctuated, RandomWords, WordGenerator};
+
+// The generators are random, so each property is checked over many words.
+const SAMPLES: usize = 10_000;
+
+// Endlessly repeats one lowercase word.
+struct Repeat(&'static str);
+
+impl WordGenerator for Repeat {
+    fn next_word(&mut self) -> Option<String> {
+        Some(self.0.to_string())
+    }
+}
+
+fn take(generator: &mut impl WordGenerator, count: usize) -> Vec<String> {
+    std::iter::from_fn(|| generator.next_word()).take(count).collect()
+}
+
+#[test]
+fn random_words_never_repeat_a_word_twice_in_a_row() {
+    let mut words = RandomWords::new(&["a", "b", "c"]);
+    let words = take(&mut words, SAMPLES);
+    assert_eq!(words.len(), SAMPLES);
+    assert!(words.windows(2).all(|pair| pair[0] != pair[1]));
+    // Every word in the list gets picked.
+    for word in ["a", "b", "c"] {
+        assert!(words.iter().any(|picked| picked == word));
+    }
+}
+
+#[test]
+fn random_words_from_a_single_word_list_repeats_it() {
+    let mut words = RandomWords::new(&["only"]);
+    assert_eq!(take(&mut words, 3), ["only", "only", "only"]);
+}
+
+#[test]
+fn random_words_from_an_empty_list_runs_out() {
+    assert_eq!(RandomWords::new(&[]).next_word(), None);
+}
+
+#[test]
+fn no_words_generates_nothing() {
+    assert_eq!(NoWords.next_word(), None);
+}
+
+#[test]
+fn punctuated_words_start_sentences_with_a_capital() {
+    let mut words = Punctuated::new(Box::new(Repeat("word")));
+    let words = take(&mut words, SAMPLES);
+    assert!(words[0].starts_with('W'));
+    for pair in words.windows(2) {
+        let ends_sentence = pair[0].ends_with(['.', '?', '!']);
+        let capitalised = pair[1].starts_with('W');
+        assert_eq!(ends_sentence, capitalised, "{:?}", pair);
+    }
+}
+
+#[test]
+fn punctuated_words_have_at_most_one_punctuation_mark() {
+    let mut words = Punctuated::new(Box::new(Repeat("word")));
+    let words = take(&mut words, SAMPLES);
+    for word in &words {
+        let rest = word.to_lowercase();
+        let punctuation = rest.strip_prefix("word").expect("the word is kept");
+        assert!(["", ".", "?", "!", ",", ";"].contains(&punctuation), "{:?}", word);
+    }
+    // Roughly one word in ten ends a sentence.
+    let sentences = words.iter().filter(|word| word.ends_with('.')).count();
+    assert!((SAMPLES / 20..SAMPLES / 5).contains(&sentences), "{}", sentences);
+}
+
+#[test]
+fn punctuated_words_run_out_with_the_inner_generator() {
+    let mut words = Punctuated::new(Box::new(NoWords));
+    assert_eq!(words.next_word(), None);
+}
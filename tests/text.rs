@@ -0,0 +1,70 @@
+use o4t::clock::ManualClock;
+use o4t::game::{Game, GameConfig, GameState, InputEvent, Mode};
+use o4t::generator::{normalize_text, TextWords, WordGenerator};
+use std::rc::Rc;
+use std::time::Duration;
+
+#[test]
+fn whitespace_is_collapsed() {
+    let text = "\u{feff}  One\ttwo\r\n\n three\u{00a0}four\u{200b}five \u{7} ";
+    assert_eq!(normalize_text(text, false), "One two three fourfive");
+}
+
+#[test]
+fn typographic_punctuation_is_only_replaced_with_ascii() {
+    let text = "\u{201c}It\u{2019}s fine\u{2026}\u{201d} \u{2014} caf\u{e9}";
+    assert_eq!(normalize_text(text, false), text);
+    assert_eq!(normalize_text(text, true), "\"It's fine...\" - caf\u{e9}");
+}
+
+#[test]
+fn text_words_run_out() {
+    let mut words = TextWords::new("a  b\nc");
+    let words = std::iter::from_fn(|| words.next_word()).collect::<Vec<_>>();
+    assert_eq!(words, ["a", "b", "c"]);
+}
+
+#[test]
+fn game_finishes_after_the_last_word() {
+    let clock = ManualClock::new();
+    let config = GameConfig {
+        mode: Mode::Time,
+        time: Duration::from_secs(30),
+        sudden_death: false,
+        min_accuracy: 0,
+    };
+    let words = Box::new(TextWords::new("to be"));
+    let mut game = Game::new(config, words, Rc::new(clock.clone()));
+    for char in "to be".chars() {
+        game.handle_input(InputEvent::Char(char));
+    }
+    assert!(game.is_active());
+    clock.advance(Duration::from_secs(2));
+    game.handle_input(InputEvent::Char(' '));
+    assert_eq!(game.state(), GameState::Finished(None));
+    assert_eq!(game.elapsed(), Duration::from_secs(2));
+}
+
+#[test]
+fn words_with_accents_are_scored_by_character() {
+    let clock = ManualClock::new();
+    let config = GameConfig {
+        mode: Mode::Time,
+        time: Duration::from_secs(30),
+        sudden_death: false,
+        min_accuracy: 0,
+    };
+    let words = Box::new(TextWords::new("café naïve"));
+    let mut game = Game::new(config, words, Rc::new(clock.clone()));
+    for char in "café naïve".chars() {
+        game.handle_input(InputEvent::Char(char));
+    }
+    clock.advance(Duration::from_secs(1));
+    game.handle_input(InputEvent::Char(' '));
+    assert_eq!(game.state(), GameState::Finished(None));
+    let score = game.score();
+    assert_eq!(score.character_hits, 9);
+    assert_eq!(score.character_misses, 0);
+    assert_eq!(score.character_matches, 9);
+    assert_eq!(score.num_words, 2);
+}
@@ -0,0 +1,158 @@
+#![cfg(feature = "tui")]
+
+use o4t::wrap::{LineComposer, LineTruncator, WordWrapper};
+use proptest::prelude::*;
+use ratatui::layout::Alignment;
+use ratatui::style::{Color, Style};
+use ratatui::text::StyledGrapheme;
+use unicode_width::UnicodeWidthStr;
+
+// Graphemes which exercise the wrapping logic: plain and wide characters, zero-width ones,
+// and the various kinds of whitespace (including wide ideographic spaces; NBSP doesn't break
+// words, and ZWSP does).
+const GRAPHEMES: [&str; 14] = [
+    "a", "b", "c", "W", ".", " ", "\u{3000}", "\u{00a0}", "\u{200b}", "\u{200d}", "e\u{301}", "你",
+    "😀", "\t",
+];
+
+// Every grapheme gets a unique colour, so we can tell where each output grapheme came from.
+fn tagged(symbol: &'static str, index: usize) -> StyledGrapheme<'static> {
+    let [_, r, g, b] = (index as u32).to_be_bytes();
+    StyledGrapheme::new(symbol, Style::default().fg(Color::Rgb(r, g, b)))
+}
+
+fn tag(grapheme: &StyledGrapheme) -> usize {
+    match grapheme.style.fg {
+        Some(Color::Rgb(r, g, b)) => u32::from_be_bytes([0, r, g, b]) as usize,
+        _ => panic!("untagged grapheme {:?}", grapheme),
+    }
+}
+
+fn lines_strategy() -> impl Strategy<Value = Vec<Vec<&'static str>>> {
+    let grapheme = prop::sample::select(GRAPHEMES.to_vec());
+    prop::collection::vec(prop::collection::vec(grapheme, 0..40), 1..5)
+}
+
+// The input lines, with each grapheme tagged by its position across all the lines.
+fn tag_lines(lines: &[Vec<&'static str>]) -> Vec<Vec<StyledGrapheme<'static>>> {
+    let mut index = 0;
+    lines
+        .iter()
+        .map(|line| {
+            line.iter()
+                .map(|symbol| {
+                    index += 1;
+                    tagged(symbol, index - 1)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+struct Output {
+    graphemes: Vec<(usize, &'static str)>,
+    width: u16,
+    alignment: Alignment,
+}
+
+fn collect(mut composer: impl LineComposer<'static>) -> Vec<Output> {
+    let mut lines = vec![];
+    while let Some(line) = composer.next_line() {
+        lines.push(Output {
+            graphemes: line.line.iter().map(|g| (tag(g), g.symbol)).collect(),
+            width: line.width,
+            alignment: line.alignment,
+        });
+    }
+    lines
+}
+
+fn wrap(lines: &[Vec<StyledGrapheme<'static>>], width: u16, trim: bool) -> Vec<Output> {
+    let input = lines
+        .iter()
+        .map(|line| (line.clone().into_iter(), Alignment::Left));
+    collect(WordWrapper::new(input, width, trim))
+}
+
+fn truncate(lines: &[Vec<StyledGrapheme<'static>>], width: u16) -> Vec<Output> {
+    let input = lines
+        .iter()
+        .map(|line| (line.clone().into_iter(), Alignment::Left));
+    collect(LineTruncator::new(input, width))
+}
+
+fn is_whitespace(symbol: &str) -> bool {
+    symbol == "\u{200b}" || symbol.chars().all(char::is_whitespace) && symbol != "\u{00a0}"
+}
+
+proptest! {
+    #[test]
+    fn wrapped_lines_fit(lines in lines_strategy(), width in 1..20u16, trim: bool) {
+        for line in wrap(&tag_lines(&lines), width, trim) {
+            let actual_width: u16 =
+                line.graphemes.iter().map(|(_, symbol)| symbol.width() as u16).sum();
+            prop_assert_eq!(line.width, actual_width);
+            prop_assert!(line.width <= width, "{:?} is wider than {}", line, width);
+        }
+    }
+
+    #[test]
+    fn wrapping_keeps_every_word_grapheme_once(
+        lines in lines_strategy(),
+        width in 1..20u16,
+        trim: bool,
+    ) {
+        let input = tag_lines(&lines);
+        let output = wrap(&input, width, trim);
+        let indices = output
+            .iter()
+            .flat_map(|line| line.graphemes.iter().map(|(index, _)| *index))
+            .collect::<Vec<_>>();
+        // Graphemes come out in order, and none are duplicated.
+        prop_assert!(indices.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", output);
+
+        // Only whitespace (at the wrap points) and graphemes which could never fit are dropped.
+        for grapheme in input.iter().flatten() {
+            let kept = indices.binary_search(&tag(grapheme)).is_ok();
+            let droppable = is_whitespace(grapheme.symbol)
+                || grapheme.symbol.width() as u16 > width;
+            prop_assert!(kept || droppable, "lost {:?} from {:?}", grapheme, output);
+        }
+    }
+
+    #[test]
+    fn wrapping_is_stable(lines in lines_strategy(), width in 1..20u16, trim: bool) {
+        let input = tag_lines(&lines);
+        let output = wrap(&input, width, trim);
+        prop_assert_eq!(&output, &wrap(&input, width, trim));
+        // Every input line produces at least one output line.
+        prop_assert!(output.len() >= lines.len());
+    }
+
+    #[test]
+    fn truncated_lines_are_prefixes(lines in lines_strategy(), width in 1..20u16) {
+        let input = tag_lines(&lines);
+        let output = truncate(&input, width);
+        prop_assert_eq!(output.len(), input.len());
+        for (line, original) in output.iter().zip(&input) {
+            prop_assert!(line.width <= width, "{:?} is wider than {}", line, width);
+            // Graphemes too wide for any line are skipped, and the rest are kept up to the
+            // point where the line is full.
+            let expected = original
+                .iter()
+                .filter(|grapheme| grapheme.symbol.width() as u16 <= width)
+                .map(|grapheme| (tag(grapheme), grapheme.symbol))
+                .take(line.graphemes.len())
+                .collect::<Vec<_>>();
+            prop_assert_eq!(&line.graphemes, &expected);
+        }
+    }
+}
+
+#[test]
+fn zero_width_produces_no_lines() {
+    let input = tag_lines(&[vec!["a", " ", "b"]]);
+    assert_eq!(wrap(&input, 0, false), vec![]);
+    assert_eq!(truncate(&input, 0), vec![]);
+}